@@ -0,0 +1 @@
+LLLLLURRRRRRRRRRULLLLLLLLLLU
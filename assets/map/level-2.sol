@@ -0,0 +1 @@
+LLLLLURRRRRRRRRRRRURRRRULLLLLLLLLLLU
@@ -0,0 +1 @@
+LLLLLLLURRRRRRRRRRRRRRRULLLLLLLLLLU
@@ -0,0 +1 @@
+LLLLLLRRURRRRRRRRRRRULLLLULRRRRRRRRRRRRRRRURRRRRRRRRRU
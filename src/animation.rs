@@ -0,0 +1,136 @@
+use bevy::{app::{App, Plugin, Update}, ecs::system::{Query, Res}, sprite::TextureAtlasSprite, time::Time, utils::HashMap};
+use rand::Rng;
+
+use crate::*;
+
+pub struct SpriteAnimationPlugin;
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        // le temps virtuel s'arrête pendant la pause, les animations aussi
+        app.add_systems(Update, animate_sprites);
+    }
+}
+
+/// Une suite d'images d'un atlas
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    pub frames: Vec<usize>,
+    /// Durée de chaque image, en secondes
+    pub frame_duration: f32,
+    /// Sinon l'animation s'arrête sur la dernière image
+    pub looping: bool,
+}
+
+impl AnimationClip {
+    pub fn still(frame: usize) -> AnimationClip {
+        return AnimationClip { frames: vec![frame], frame_duration: 1., looping: false };
+    }
+
+    pub fn looping(frames: &[usize], frame_duration: f32) -> AnimationClip {
+        return AnimationClip { frames: frames.to_vec(), frame_duration, looping: true };
+    }
+
+    pub fn once(frames: &[usize], frame_duration: f32) -> AnimationClip {
+        return AnimationClip { frames: frames.to_vec(), frame_duration, looping: false };
+    }
+}
+
+/// Animation d'un sprite d'atlas, le premier clip ajouté est joué au départ
+#[derive(Component, Clone, Debug)]
+pub struct SpriteAnimation {
+    clips: HashMap<&'static str, AnimationClip>,
+    current: &'static str,
+    frame: usize,
+    elapsed: f32,
+}
+
+impl SpriteAnimation {
+    pub fn new(name: &'static str, clip: AnimationClip) -> SpriteAnimation {
+        let mut clips = HashMap::new();
+        clips.insert(name, clip);
+        return SpriteAnimation { clips, current: name, frame: 0, elapsed: 0. };
+    }
+
+    pub fn with_clip(mut self, name: &'static str, clip: AnimationClip) -> SpriteAnimation {
+        self.clips.insert(name, clip);
+        return self;
+    }
+
+    /// Ne recommence pas le clip s'il est déjà en cours
+    pub fn play(&mut self, name: &'static str) {
+        if self.current == name { return; }
+        if !self.clips.contains_key(name) {
+            warn!("unknown animation clip: {}", name);
+            return;
+        }
+        self.current = name;
+        self.frame = 0;
+        self.elapsed = 0.;
+    }
+
+    pub fn current(&self) -> &'static str {
+        return self.current;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        let clip = &self.clips[self.current];
+        return !clip.looping && self.frame + 1 >= clip.frames.len();
+    }
+
+    /// Index dans l'atlas de l'image affichée
+    pub fn atlas_index(&self) -> usize {
+        return self.clips[self.current].frames[self.frame];
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        let clip = &self.clips[self.current];
+        let (frame_duration, frame_count, looping) = (clip.frame_duration, clip.frames.len(), clip.looping);
+        self.elapsed += delta;
+        while self.elapsed >= frame_duration {
+            self.elapsed -= frame_duration;
+            if self.frame + 1 < frame_count {
+                self.frame += 1;
+            } else if looping {
+                self.frame = 0;
+            } else {
+                self.elapsed = 0.;
+                break;
+            }
+        }
+    }
+}
+
+// Atlas : hero-sheet.png = [face, gauche 1, gauche 2, droite 1, droite 2]
+pub fn player_animation() -> SpriteAnimation {
+    return SpriteAnimation::new("idle", AnimationClip::still(0))
+        .with_clip("left", AnimationClip::looping(&[1, 2], 0.15))
+        .with_clip("right", AnimationClip::looping(&[3, 4], 0.15));
+}
+
+// Atlas : bat-sheet.png = [gauche 1, gauche 2, droite 1, droite 2]
+pub fn bat_animation() -> SpriteAnimation {
+    return SpriteAnimation::new("right", AnimationClip::looping(&[2, 3], 0.3))
+        .with_clip("left", AnimationClip::looping(&[0, 1], 0.3));
+}
+
+// Atlas : chest-sheet.png = [fermé, ..., ouvert]
+pub fn chest_animation() -> SpriteAnimation {
+    return SpriteAnimation::new("closed", AnimationClip::still(0))
+        .with_clip("open", AnimationClip::once(&[1, 2, 3], 0.25));
+}
+
+// Atlas : torch-sheet.png = 3 flammes
+pub fn torch_animation() -> SpriteAnimation {
+    // chaque torche a son propre rythme pour ne pas clignoter en même temps
+    let duration = rand::thread_rng().gen_range(0.1..0.2);
+    return SpriteAnimation::new("flicker", AnimationClip::looping(&[0, 1, 2, 1], duration));
+}
+
+fn animate_sprites(time: Res<Time>, mut query: Query<(&mut SpriteAnimation, &mut TextureAtlasSprite)>) {
+    for (mut animation, mut sprite) in query.iter_mut() {
+        animation.advance(time.delta_seconds());
+        if sprite.index != animation.atlas_index() {
+            sprite.index = animation.atlas_index();
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Outil de relecture des niveaux, sans fenêtre :
+//! `dungeon-level validate|render|solve|stats|convert <fichiers>`
+
+#![allow(clippy::needless_return)]
+
+use std::{fs, process::ExitCode};
+
+use dungeon::{format_moves, LevelAsset, LevelGrid, Simulation, StepResult, Tile, SOLUTION_EXTENSION};
+
+const USAGE: &str = "usage: dungeon-level <command> <level.lev>...
+
+commands:
+  validate <level.lev>...          check tiles, size, doors and the player
+  render [--color] <level.lev>...  print the map, with ANSI colours if asked
+  solve [--write] <level.lev>...   print the shortest solution (L, R, U),
+                                   --write saves it next to the level as .sol
+  stats <level.lev>...             chests, door pairs, coins and reachable cells
+  convert <level.lev> [out.ron]    export the level as RON, to stdout by default;
+                                   export only, the game reads .lev files";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, args)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let result = match command.as_str() {
+        "validate" => for_each_level(args, validate),
+        "render" => {
+            let color = args.iter().any(|arg| arg == "--color");
+            let files: Vec<String> = args.iter().filter(|arg| *arg != "--color").cloned().collect();
+            for_each_level(&files, |path, asset| render(path, asset, color))
+        }
+        "solve" => {
+            let write = args.iter().any(|arg| arg == "--write");
+            let files: Vec<String> = args.iter().filter(|arg| *arg != "--write").cloned().collect();
+            for_each_level(&files, |path, asset| solve(path, asset, write))
+        }
+        "stats" => for_each_level(args, stats),
+        "convert" => convert(args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("unknown command '{}'\n\n{}", command, USAGE)),
+    };
+
+    return match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    };
+}
+
+fn load(path: &str) -> Result<LevelAsset, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+    return Ok(LevelAsset::parse(&text));
+}
+
+fn grid(path: &str, asset: &LevelAsset) -> Result<LevelGrid, String> {
+    return LevelGrid::parse(&asset.map).map_err(|error| format!("{}: {}", path, error));
+}
+
+/// Continue sur les fichiers suivants et échoue à la fin si un seul a échoué
+fn for_each_level(files: &[String], mut command: impl FnMut(&str, &LevelAsset) -> Result<(), String>) -> Result<(), String> {
+    if files.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+    let mut failed = 0;
+    for path in files {
+        if let Err(error) = load(path).and_then(|asset| command(path, &asset)) {
+            eprintln!("{}", error);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} levels failed", failed, files.len()));
+    }
+    return Ok(());
+}
+
+fn validate(path: &str, asset: &LevelAsset) -> Result<(), String> {
+    let errors = grid(path, asset)?.validate();
+    if errors.is_empty() {
+        println!("{}: ok", path);
+        return Ok(());
+    }
+    let lines: Vec<String> = errors.iter().map(|error| format!("{}: {}", path, error)).collect();
+    return Err(lines.join("\n"));
+}
+
+fn render(path: &str, asset: &LevelAsset, color: bool) -> Result<(), String> {
+    let grid = grid(path, asset)?;
+    println!("{} - {}", path, asset.name.as_deref().unwrap_or("sans nom"));
+    print!("{}", grid.render(color));
+    return Ok(());
+}
+
+fn solve(path: &str, asset: &LevelAsset, write: bool) -> Result<(), String> {
+    let simulation = Simulation::new(&grid(path, asset)?);
+    let Some(moves) = simulation.solve() else {
+        return Err(format!("{}: no solution found", path));
+    };
+    let par = match asset.par {
+        Some(par) => format!(", par {}", par),
+        None => String::new(),
+    };
+    println!("{}: {} moves{}", path, moves.len(), par);
+    println!("{}", format_moves(&moves));
+    if write {
+        let solution_path = solution_path(path);
+        fs::write(&solution_path, format_moves(&moves) + "\n").map_err(|error| format!("{}: {}", solution_path, error))?;
+        println!("written to {}", solution_path);
+    }
+    return Ok(());
+}
+
+/// `level-1.lev` -> `level-1.sol`
+fn solution_path(path: &str) -> String {
+    return std::path::Path::new(path).with_extension(SOLUTION_EXTENSION).to_string_lossy().into_owned();
+}
+
+fn stats(path: &str, asset: &LevelAsset) -> Result<(), String> {
+    let grid = grid(path, asset)?;
+    let simulation = Simulation::new(&grid);
+    let blue_doors = grid.positions(Tile::BlueDoor).len();
+    let air_cells = grid.positions(Tile::Air).len() + grid.positions(Tile::Player).len()
+        + grid.positions(Tile::Coin).len() + grid.positions(Tile::Torch).len()
+        + grid.positions(Tile::Chest).len() + blue_doors + grid.positions(Tile::RedDoor).len();
+
+    println!("{} - {}", path, asset.name.as_deref().unwrap_or("sans nom"));
+    println!("  chests:          {}", simulation.chests.len());
+    println!("  blue doors:      {} ({} pair{})", blue_doors, blue_doors / 2, if blue_doors / 2 == 1 {""} else {"s"});
+    println!("  coins:           {}", grid.positions(Tile::Coin).len());
+    println!("  torches:         {}", grid.positions(Tile::Torch).len());
+    println!("  reachable cells: {} of {} open", simulation.reachable_cells().len(), air_cells);
+    if let StepResult::Playing(start) = simulation.start() {
+        println!("  start:           {:?}", start.player);
+    }
+    return Ok(());
+}
+
+/// Export pour d'autres outils : aucun loader du jeu ne lit ce RON
+fn convert(args: &[String]) -> Result<(), String> {
+    let Some(path) = args.first() else { return Err(USAGE.to_owned()); };
+    let asset = load(path)?;
+    let ron = ron::ser::to_string_pretty(&asset, ron::ser::PrettyConfig::default()).map_err(|error| format!("{}: {}", path, error))?;
+    match args.get(1) {
+        Some(output) => fs::write(output, ron + "\n").map_err(|error| format!("{}: {}", output, error))?,
+        None => println!("{}", ron),
+    }
+    return Ok(());
+}
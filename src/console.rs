@@ -0,0 +1,255 @@
+use std::collections::VecDeque;
+
+use bevy::{app::{App, Plugin, PreUpdate, Startup, Update}, ecs::{event::{EventReader, EventWriter}, query::{With, Without}, schedule::{common_conditions::in_state, IntoSystemConfigs}, system::{Commands, Query, Res, ResMut, Resource}}, hierarchy::BuildChildren, input::{keyboard::KeyCode, Input}, render::{color::Color, view::Visibility}, text::{Text, TextStyle}, transform::components::Transform, ui::{node_bundles::{NodeBundle, TextBundle}, PositionType, Style, UiRect, Val, ZIndex}, window::ReceivedCharacter};
+
+use crate::*;
+
+/// Lignes gardées à l'écran
+const CONSOLE_HISTORY: usize = 10;
+const CONSOLE_KEY: KeyCode = KeyCode::F1;
+
+const HELP: &str = "level <n> | tp <x> <y> | spawn bat <x> <y> | open chests | noclip | god | tick | reload";
+
+pub struct ConsolePlugin;
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Console>()
+            .init_resource::<DevCheats>()
+            .add_systems(Startup, spawn_console)
+            // après la lecture des touches pour que le jeu ne voie rien de ce qui est tapé
+            .add_systems(PreUpdate, console_input.after(update_action_state))
+            .add_systems(Update, (
+                run_console_commands.run_if(in_state(GameState::Game)),
+                draw_console,
+            ).chain());
+    }
+}
+
+/// Triches activées depuis la console, lues par le jeu seulement avec la feature `dev`
+#[derive(Resource, Default)]
+pub struct DevCheats {
+    /// Les murs ne bloquent plus et le joueur ne tombe plus
+    pub noclip: bool,
+    /// Les chauves-souris ne tuent plus
+    pub god: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    history: VecDeque<String>,
+    /// Lignes validées, pas encore exécutées
+    pending: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: String) {
+        info!("console: {}", line);
+        self.history.push_back(line);
+        while self.history.len() > CONSOLE_HISTORY {
+            self.history.pop_front();
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ConsoleCommand {
+    Level(usize),
+    Teleport(i32, i32),
+    SpawnBat(i32, i32),
+    OpenChests,
+    Noclip,
+    God,
+    Tick,
+    Reload,
+    Help,
+}
+
+impl ConsoleCommand {
+    fn parse(line: &str) -> Result<ConsoleCommand, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |word: Option<&&str>| -> Result<i32, String> {
+            let word = word.ok_or("missing number")?;
+            return word.parse().map_err(|_| format!("not a number: {}", word));
+        };
+        return match words.as_slice() {
+            ["level", rest @ ..] => {
+                let level = number(rest.first())?;
+                if !(1..=NB_LEVEL).contains(&level) {
+                    return Err(format!("levels go from 1 to {}", NB_LEVEL));
+                }
+                Ok(ConsoleCommand::Level(level as usize))
+            }
+            ["tp", rest @ ..] => Ok(ConsoleCommand::Teleport(number(rest.first())?, number(rest.get(1))?)),
+            ["spawn", "bat", rest @ ..] => Ok(ConsoleCommand::SpawnBat(number(rest.first())?, number(rest.get(1))?)),
+            ["open", "chests"] => Ok(ConsoleCommand::OpenChests),
+            ["noclip"] => Ok(ConsoleCommand::Noclip),
+            ["god"] => Ok(ConsoleCommand::God),
+            ["tick"] => Ok(ConsoleCommand::Tick),
+            ["reload"] => Ok(ConsoleCommand::Reload),
+            ["help"] => Ok(ConsoleCommand::Help),
+            _ => Err(format!("unknown command: {}", line)),
+        };
+    }
+}
+
+#[derive(Component)]
+struct ConsoleRoot;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn spawn_console(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.),
+                left: Val::Px(0.),
+                width: Val::Percent(100.),
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.8).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+        ConsoleRoot,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 18.,
+                color: Color::rgb(0.6, 1., 0.6),
+                ..default()
+            }),
+            ConsoleText,
+        ));
+    });
+}
+
+fn console_input(
+    keyboard: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+    mut actions: ResMut<ActionState>,
+) {
+    if keyboard.just_pressed(CONSOLE_KEY) {
+        console.open = !console.open;
+        characters.clear();
+    }
+    if !console.open {
+        characters.clear();
+        return;
+    }
+    *actions = ActionState::default();
+
+    for character in characters.read() {
+        if !character.char.is_control() {
+            console.input.push(character.char);
+        }
+    }
+    if keyboard.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keyboard.just_pressed(KeyCode::Escape) {
+        console.open = false;
+    }
+    if keyboard.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        if !line.trim().is_empty() {
+            console.pending.push(line);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_console_commands(
+    mut commands: Commands,
+    mut console: ResMut<Console>,
+    mut cheats: ResMut<DevCheats>,
+    mut level_res: ResMut<CurrentLevel>,
+    level_maps: Res<LevelMaps>,
+    asset_server: Res<AssetServer>,
+    textures_ressource: Res<TexturesRessource>,
+    mut simulation: ResMut<SimulationState>,
+    mut player: Query<(&mut Player, &mut Transform)>,
+    mut chest_query: Query<&mut Chest>,
+    monster_query: Query<&Monster, Without<Player>>,
+    mut change_level_event: EventWriter<ChangeLevelEvent>,
+    mut tick_event: EventWriter<TickEvent>,
+) {
+    for line in std::mem::take(&mut console.pending) {
+        console.print(format!("> {}", line));
+        let command = match ConsoleCommand::parse(&line) {
+            Ok(command) => command,
+            Err(error) => {
+                console.print(error);
+                continue;
+            }
+        };
+
+        match command {
+            ConsoleCommand::Level(level) => {
+                level_res.level = level;
+                change_level_event.send(ChangeLevelEvent { new_level: false });
+            }
+            ConsoleCommand::Teleport(game_x, game_y) => {
+                let Ok((mut player, mut player_transform)) = player.get_single_mut() else { continue; };
+                player_transform.translation = player.move_without_animation(game_x, game_y).extend(1.);
+            }
+            ConsoleCommand::SpawnBat(game_x, game_y) => {
+                if monster_query.iter().any(|monster| monster.game_x() == game_x && monster.game_y() == game_y) {
+                    console.print(format!("a bat is already at {} {}", game_x, game_y));
+                    continue;
+                }
+                let order = simulation.next_monster;
+                simulation.next_monster += 1;
+                spawn_bat(&mut commands, &textures_ressource, game_x, game_y, order);
+            }
+            ConsoleCommand::OpenChests => {
+                // ouverts sans libérer de chauve-souris
+                for mut chest in chest_query.iter_mut() {
+                    chest.is_open = true;
+                    chest.has_spawn = true;
+                }
+            }
+            ConsoleCommand::Noclip => {
+                cheats.noclip = !cheats.noclip;
+                console.print(format!("noclip {}", if cheats.noclip {"on"} else {"off"}));
+            }
+            ConsoleCommand::God => {
+                cheats.god = !cheats.god;
+                console.print(format!("god {}", if cheats.god {"on"} else {"off"}));
+            }
+            ConsoleCommand::Tick => {
+                tick_event.send(TickEvent);
+            }
+            ConsoleCommand::Reload => {
+                let Some(path) = level_maps.maps_handle.get(level_res.level-1).and_then(|handle| handle.path()) else { continue; };
+                // le niveau est reconstruit par `reload_modified_level` une fois la carte relue
+                asset_server.reload(path.clone());
+                console.print(format!("reloading {}", path));
+            }
+            ConsoleCommand::Help => console.print(HELP.to_owned()),
+        }
+    }
+}
+
+fn draw_console(
+    console: Res<Console>,
+    mut root: Query<&mut Visibility, With<ConsoleRoot>>,
+    mut text: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() { return; }
+    for mut visibility in root.iter_mut() {
+        *visibility = if console.open { Visibility::Visible } else { Visibility::Hidden };
+    }
+    let mut lines: Vec<String> = console.history.iter().cloned().collect();
+    lines.push(format!("> {}_", console.input));
+    for mut text in text.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
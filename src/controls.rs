@@ -0,0 +1,329 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::{app::{App, Plugin, PreUpdate}, ecs::{event::EventReader, schedule::IntoSystemConfigs, system::{Res, ResMut, Resource}}, input::{gamepad::{GamepadButton, GamepadButtonType}, keyboard::KeyCode, Input, InputSystem}, log::warn};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+const BINDINGS_FILE: &str = "bindings.ron";
+
+pub struct ControlsPlugin;
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let storage: Box<dyn SaveStorage> = Box::new(FileStorage::named(BINDINGS_FILE));
+        #[cfg(target_arch = "wasm32")]
+        let storage: Box<dyn SaveStorage> = Box::new(MemoryStorage::default());
+
+        app
+            .insert_resource(Controls::load(storage))
+            .init_resource::<ActionState>()
+            .init_resource::<Rebinding>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystem).after(read_gamepad).after(recognize_gestures));
+    }
+}
+
+/// Ce que le joueur veut faire, quelle que soit la touche
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Use,
+    Undo,
+    Restart,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [Action::MoveLeft, Action::MoveRight, Action::Use, Action::Undo, Action::Restart, Action::Pause];
+
+    pub fn label(&self) -> &'static str {
+        return match self {
+            Action::MoveLeft => "Gauche",
+            Action::MoveRight => "Droite",
+            Action::Use => "Porte / coffre",
+            Action::Undo => "Annuler",
+            Action::Restart => "Recommencer",
+            Action::Pause => "Pause",
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Preset {
+    Arrows,
+    Wasd,
+    Vim,
+}
+
+impl Preset {
+    pub fn label(&self) -> &'static str {
+        return match self {
+            Preset::Arrows => "Flèches",
+            Preset::Wasd => "WASD",
+            Preset::Vim => "Vim (hjkl)",
+        };
+    }
+
+    pub fn next(&self, step: i32) -> Preset {
+        let presets = [Preset::Arrows, Preset::Wasd, Preset::Vim];
+        let index = presets.iter().position(|preset| preset == self).unwrap_or(0) as i32;
+        return presets[(index + step).rem_euclid(presets.len() as i32) as usize];
+    }
+
+    fn keys(&self, action: Action) -> Vec<KeyCode> {
+        return match (self, action) {
+            (Preset::Arrows, Action::MoveLeft) => vec![KeyCode::Left],
+            (Preset::Arrows, Action::MoveRight) => vec![KeyCode::Right],
+            (Preset::Arrows, Action::Use) => vec![KeyCode::Up],
+            (Preset::Wasd, Action::MoveLeft) => vec![KeyCode::A],
+            (Preset::Wasd, Action::MoveRight) => vec![KeyCode::D],
+            (Preset::Wasd, Action::Use) => vec![KeyCode::W],
+            (Preset::Vim, Action::MoveLeft) => vec![KeyCode::H],
+            (Preset::Vim, Action::MoveRight) => vec![KeyCode::L],
+            (Preset::Vim, Action::Use) => vec![KeyCode::K],
+            (_, Action::Undo) => vec![KeyCode::U],
+            (_, Action::Restart) => vec![KeyCode::R],
+            (_, Action::Pause) => vec![KeyCode::Escape],
+        };
+    }
+}
+
+/// Touches qu'on peut choisir, leur nom `Debug` est écrit dans le fichier
+const BINDABLE_KEYS: [KeyCode; 52] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Left, KeyCode::Right, KeyCode::Up, KeyCode::Down,
+    KeyCode::Space, KeyCode::Return, KeyCode::Escape, KeyCode::Back, KeyCode::Tab, KeyCode::Delete,
+    KeyCode::ShiftLeft, KeyCode::ShiftRight, KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::AltLeft, KeyCode::AltRight,
+];
+
+const BINDABLE_BUTTONS: [GamepadButtonType; 14] = [
+    GamepadButtonType::South, GamepadButtonType::East, GamepadButtonType::North, GamepadButtonType::West,
+    GamepadButtonType::LeftTrigger, GamepadButtonType::RightTrigger, GamepadButtonType::LeftTrigger2, GamepadButtonType::RightTrigger2,
+    GamepadButtonType::Select, GamepadButtonType::Start,
+    GamepadButtonType::DPadUp, GamepadButtonType::DPadDown, GamepadButtonType::DPadLeft, GamepadButtonType::DPadRight,
+];
+
+pub fn key_name(key: KeyCode) -> String {
+    return format!("{:?}", key);
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    return BINDABLE_KEYS.iter().copied().find(|key| key_name(*key) == name);
+}
+
+pub fn is_bindable(key: KeyCode) -> bool {
+    return BINDABLE_KEYS.contains(&key);
+}
+
+fn button_from_name(name: &str) -> Option<GamepadButtonType> {
+    return BINDABLE_BUTTONS.iter().copied().find(|button| format!("{:?}", button) == name);
+}
+
+/// Contenu du fichier de commandes, les touches sont écrites par leur nom
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Bindings {
+    pub preset: Preset,
+    pub keyboard: BTreeMap<Action, Vec<String>>,
+    pub gamepad: BTreeMap<Action, Vec<String>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        return Bindings::from_preset(Preset::Arrows);
+    }
+}
+
+impl Bindings {
+    pub fn from_preset(preset: Preset) -> Bindings {
+        let keyboard = Action::ALL.iter()
+            .map(|action| (*action, preset.keys(*action).into_iter().map(key_name).collect()))
+            .collect();
+        // la croix et le stick déplacent toujours le joueur, en plus de ces boutons
+        let gamepad = Action::ALL.iter()
+            .map(|action| {
+                let button = match action {
+                    Action::MoveLeft => GamepadButtonType::DPadLeft,
+                    Action::MoveRight => GamepadButtonType::DPadRight,
+                    Action::Use => GamepadButtonType::South,
+                    Action::Undo => GamepadButtonType::West,
+                    Action::Restart => GamepadButtonType::Select,
+                    Action::Pause => GamepadButtonType::Start,
+                };
+                (*action, vec![format!("{:?}", button)])
+            })
+            .collect();
+        return Bindings { preset, keyboard, gamepad };
+    }
+
+    pub fn keys(&self, action: Action) -> Vec<KeyCode> {
+        return self.keyboard.get(&action).into_iter().flatten().filter_map(|name| key_from_name(name)).collect();
+    }
+
+    pub fn buttons(&self, action: Action) -> Vec<GamepadButtonType> {
+        return self.gamepad.get(&action).into_iter().flatten().filter_map(|name| button_from_name(name)).collect();
+    }
+
+    /// Texte des touches d'une action, pour les menus et le HUD
+    pub fn keys_label(&self, action: Action) -> String {
+        let keys: Vec<String> = self.keys(action).into_iter().map(key_name).collect();
+        if keys.is_empty() { return "-".to_owned(); }
+        return keys.join(" / ");
+    }
+
+    /// Noms des boutons façon manette Xbox
+    pub fn buttons_label(&self, action: Action) -> String {
+        let buttons: Vec<String> = self.buttons(action).into_iter().map(|button| match button {
+            GamepadButtonType::South => "A".to_owned(),
+            GamepadButtonType::East => "B".to_owned(),
+            GamepadButtonType::West => "X".to_owned(),
+            GamepadButtonType::North => "Y".to_owned(),
+            GamepadButtonType::LeftTrigger => "LB".to_owned(),
+            GamepadButtonType::RightTrigger => "RB".to_owned(),
+            GamepadButtonType::LeftTrigger2 => "LT".to_owned(),
+            GamepadButtonType::RightTrigger2 => "RT".to_owned(),
+            other => format!("{:?}", other),
+        }).collect();
+        if buttons.is_empty() { return "-".to_owned(); }
+        return buttons.join(" / ");
+    }
+
+    /// Une touche ne sert qu'à une action : elle est retirée des autres
+    pub fn rebind(&mut self, action: Action, key: KeyCode) {
+        let name = key_name(key);
+        for keys in self.keyboard.values_mut() {
+            keys.retain(|other| *other != name);
+        }
+        self.keyboard.insert(action, vec![name]);
+    }
+
+    pub fn from_ron(content: &str) -> Result<Bindings, ron::error::SpannedError> {
+        return ron::from_str(content);
+    }
+
+    pub fn to_ron(&self) -> String {
+        return ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap();
+    }
+}
+
+/// Les commandes du joueur et l'endroit où elles sont enregistrées
+#[derive(Resource)]
+pub struct Controls {
+    pub bindings: Bindings,
+    storage: Box<dyn SaveStorage>,
+}
+
+impl Controls {
+    pub fn load(storage: Box<dyn SaveStorage>) -> Controls {
+        let bindings = match storage.read() {
+            Some(content) => Bindings::from_ron(&content).unwrap_or_else(|error| {
+                warn!("could not read bindings, using defaults: {}", error);
+                Bindings::default()
+            }),
+            None => Bindings::default(),
+        };
+        return Controls { bindings, storage };
+    }
+
+    pub fn write(&self) {
+        if let Err(error) = self.storage.write(&self.bindings.to_ron()) {
+            warn!("could not write bindings: {}", error);
+        }
+    }
+}
+
+/// Action en attente d'une nouvelle touche dans le menu des commandes
+#[derive(Resource, Default)]
+pub struct Rebinding {
+    pub action: Option<Action>,
+}
+
+/// Actions du joueur pour cette image, tous périphériques confondus
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl ActionState {
+    /// Maintenue, ou glissement / tape pendant cette image
+    pub fn pressed(&self, action: Action) -> bool {
+        return self.pressed.contains(&action);
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        return self.just_pressed.contains(&action);
+    }
+
+    /// Aussi utilisé par les tests pour jouer sans clavier
+    pub fn press(&mut self, action: Action, just_pressed: bool) {
+        self.pressed.insert(action);
+        if just_pressed {
+            self.just_pressed.insert(action);
+        }
+    }
+}
+
+/// Action d'un geste sur l'écran tactile ou avec la souris
+fn gesture_action(gesture: Gesture) -> Action {
+    return match gesture {
+        Gesture::SwipeLeft => Action::MoveLeft,
+        Gesture::SwipeRight => Action::MoveRight,
+        Gesture::Tap | Gesture::SwipeUp | Gesture::SwipeDown => Action::Use,
+        Gesture::LongPress => Action::Pause,
+        Gesture::TwoFingerTap => Action::Undo,
+    };
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_action_state(
+    controls: Res<Controls>,
+    rebinding: Res<Rebinding>,
+    keyboard: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    active_gamepad: Res<ActiveGamepad>,
+    gamepad: Res<GamepadActions>,
+    mut gestures: EventReader<GestureEvent>,
+    gesture_tracker: Res<GestureTracker>,
+    mut actions: ResMut<ActionState>,
+) {
+    *actions = ActionState::default();
+    // la touche choisie dans le menu ne doit rien déclencher
+    if rebinding.action.is_some() {
+        gestures.clear();
+        return;
+    }
+
+    for action in Action::ALL {
+        let keys = controls.bindings.keys(action);
+        if keyboard.any_pressed(keys.iter().copied()) {
+            actions.press(action, keyboard.any_just_pressed(keys.iter().copied()));
+        }
+
+        if let Some(active) = active_gamepad.gamepad {
+            let buttons: Vec<GamepadButton> = controls.bindings.buttons(action).into_iter().map(|button| GamepadButton::new(active, button)).collect();
+            if gamepad_buttons.any_pressed(buttons.iter().copied()) {
+                actions.press(action, gamepad_buttons.any_just_pressed(buttons.iter().copied()));
+            }
+        }
+    }
+    if gamepad.left {
+        actions.press(Action::MoveLeft, gamepad.left_pressed);
+    }
+    if gamepad.right {
+        actions.press(Action::MoveRight, gamepad.right_pressed);
+    }
+
+    for event in gestures.read() {
+        actions.press(gesture_action(event.gesture), true);
+    }
+    // glisser puis garder le doigt posé fait avancer comme une touche maintenue
+    if let Some(held) = gesture_tracker.held() {
+        actions.press(gesture_action(held), false);
+    }
+}
@@ -1,12 +1,98 @@
-use bevy::{app::{App, Plugin}, asset::AssetServer, ecs::{schedule::OnEnter, system::{Commands, Res}}, log::info, math::vec3, sprite::{Anchor, Sprite, SpriteBundle}, transform::components::Transform};
+use bevy::{app::{App, Plugin, Startup, Update}, asset::{io::Reader, Asset, AssetLoader, AssetServer, AsyncReadExt, Handle, LoadContext}, ecs::{entity::Entity, query::With, schedule::{common_conditions::in_state, IntoSystemConfigs, OnEnter, OnExit}, system::{Commands, Query, Res, ResMut, Resource}}, hierarchy::{BuildChildren, DespawnRecursiveExt}, log::info, math::vec3, reflect::TypePath, sprite::{Anchor, Sprite, SpriteBundle}, text::TextStyle, time::Time, transform::components::Transform, ui::{node_bundles::{NodeBundle, TextBundle}, Node, Overflow, PositionType, Style, Val}, utils::BoxedFuture};
 
 use crate::*;
 
+const CREDITS_FILE: &str = "credits.txt";
+/// Vitesse de défilement du générique, en pixels par seconde
+const CREDITS_SPEED: f32 = 30.;
+const CREDITS_HEIGHT: f32 = 220.;
+
 pub struct EndGamePlugin;
 impl Plugin for EndGamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::End), start_end_screen);
+        app
+            .init_asset::<TextAsset>()
+            .init_asset_loader::<TextAssetLoader>()
+            .add_systems(Startup, load_credits)
+            .add_systems(OnEnter(GameState::End), (start_end_screen, spawn_credits))
+            .add_systems(Update, scroll_credits.run_if(in_state(GameState::End)))
+            .add_systems(OnExit(GameState::End), despawn_end_screen);
+    }
+}
+
+/// Fichier texte brut, utilisé pour le générique
+#[derive(Asset, TypePath, Debug)]
+pub struct TextAsset {
+    pub text: String,
+}
+
+#[derive(Default)]
+pub struct TextAssetLoader;
+
+impl AssetLoader for TextAssetLoader {
+    type Asset = TextAsset;
+    type Settings = ();
+    type Error = std::io::Error;
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(TextAsset { text: String::from_utf8_lossy(&bytes).into_owned() })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["txt"]
+    }
+}
+
+#[derive(Resource)]
+pub struct Credits {
+    pub handle: Handle<TextAsset>,
+}
+
+/// Tout ce qui est affiché sur l'écran de fin
+#[derive(Component)]
+pub struct EndScreen;
+
+#[derive(Component)]
+struct CreditsText;
+
+fn load_credits(mut commands: Commands, asset_server: Res<AssetServer>, mut loading_assets: ResMut<LoadingAssets>) {
+    let handle = asset_server.load(CREDITS_FILE);
+    loading_assets.handles.push(handle.clone().untyped());
+    commands.insert_resource(Credits { handle });
+}
+
+/// Rapport de la partie, exporté depuis l'écran de fin
+pub fn run_report(run_stats: &RunStats, score: &Score, save: &SaveGame) -> String {
+    let mut report = format!("Dungeon - partie du {}\n\n", chrono::Local::now().format("%Y-%m-%d %H:%M"));
+    report.push_str(&format!("Score : {} (record : {})\n", score.total, save.data.best_total));
+    report.push_str(&format!("{}\n\n", run_stats.total_line()));
+    for line in run_stats.level_lines() {
+        report.push_str(&line);
+        report.push('\n');
     }
+    return report;
+}
+
+/// Écrit le rapport à côté de la sauvegarde et renvoie le chemin du fichier
+pub fn export_run(report: &str) -> Result<String, String> {
+    if cfg!(target_arch = "wasm32") {
+        // pas de système de fichiers dans le navigateur
+        info!("{}", report);
+        return Ok("console".to_owned());
+    }
+    let dir = data_dir();
+    std::fs::create_dir_all(&dir).map_err(|error| error.to_string())?;
+    let path = dir.join(format!("run-{}.txt", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, report).map_err(|error| error.to_string())?;
+    return Ok(path.display().to_string());
 }
 
 pub fn start_end_screen(
@@ -30,6 +116,7 @@ pub fn start_end_screen(
             },
             ..Default::default()
         },
+        EndScreen,
     ));
 
     // Big Chest
@@ -47,6 +134,7 @@ pub fn start_end_screen(
             },
             ..Default::default()
         },
+        EndScreen,
     ));
 
     // GG
@@ -64,6 +152,7 @@ pub fn start_end_screen(
             },
             ..Default::default()
         },
+        EndScreen,
     ));
     commands.spawn((
         SpriteBundle {
@@ -79,5 +168,64 @@ pub fn start_end_screen(
             },
             ..Default::default()
         },
+        EndScreen,
     ));
-}
\ No newline at end of file
+}
+
+fn spawn_credits(mut commands: Commands, credits: Res<Credits>, text_assets: Res<Assets<TextAsset>>) {
+    let text = text_assets.get(&credits.handle).map(|credits| credits.text.clone()).unwrap_or_default();
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(20.),
+                bottom: Val::Px(20.),
+                width: Val::Px(320.),
+                height: Val::Px(CREDITS_HEIGHT),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            ..default()
+        },
+        EndScreen,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section(text, TextStyle {
+                font_size: 20.,
+                color: Color::WHITE,
+                ..default()
+            }).with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(CREDITS_HEIGHT),
+                ..default()
+            }),
+            CreditsText,
+        ));
+    });
+}
+
+/// Le générique remonte puis recommence en bas
+fn scroll_credits(time: Res<Time>, mut credits_query: Query<(&mut Style, &Node), With<CreditsText>>) {
+    for (mut style, node) in credits_query.iter_mut() {
+        let Val::Px(top) = style.top else { continue; };
+        let mut top = top - CREDITS_SPEED * time.delta_seconds();
+        if top < -node.size().y {
+            top = CREDITS_HEIGHT;
+        }
+        style.top = Val::Px(top);
+    }
+}
+
+fn despawn_end_screen(
+    mut commands: Commands,
+    end_query: Query<Entity, With<EndScreen>>,
+    game_query: Query<Entity, With<InGame>>,
+    mut level_maps: ResMut<LevelMaps>,
+) {
+    for entity in end_query.iter().chain(game_query.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+    // le niveau sera reconstruit si on rejoue
+    level_maps.sended = false;
+}
@@ -0,0 +1,121 @@
+use std::{fs::{File, OpenOptions}, io::{LineWriter, Write}, path::Path};
+
+use bevy::{app::{App, Plugin, Update}, ecs::{event::EventReader, schedule::{common_conditions::in_state, IntoSystemConfigs}, system::{Local, Query, Res, ResMut, Resource}}, time::{Real, Time}};
+use serde::Serialize;
+
+use crate::*;
+
+/// Chemin du journal, absent par défaut : `DUNGEON_EVENT_LOG=partie.jsonl cargo run`
+pub const EVENT_LOG_VAR: &str = "DUNGEON_EVENT_LOG";
+
+pub struct EventLogPlugin;
+impl Plugin for EventLogPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(EventLog::from_env())
+            // après les morts de `TurnSet::EndTick`
+            .add_systems(Update, log_game_events.after(TurnSet::EndTick).run_if(in_state(GameState::Game)));
+    }
+}
+
+/// Évènements de la partie, ajoutés en JSON ligne par ligne pour analyser les sessions de test
+#[derive(Resource, Default)]
+pub struct EventLog {
+    file: Option<LineWriter<File>>,
+}
+
+/// Une ligne du journal
+#[derive(Serialize)]
+struct LogLine {
+    /// Secondes depuis le lancement du jeu
+    time: f32,
+    level: usize,
+    #[serde(flatten)]
+    event: LoggedEvent,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LoggedEvent {
+    /// Coup compté, avec la case où arrive le joueur
+    Move { action: Action, moves: u32, x: i32, y: i32 },
+    Death { moves: u32, x: i32, y: i32 },
+    /// `time` : temps passé dans le niveau
+    Completion { moves: u32, time: f32 },
+}
+
+impl EventLog {
+    pub fn from_env() -> EventLog {
+        let Some(path) = std::env::var_os(EVENT_LOG_VAR) else { return EventLog::default(); };
+        return EventLog::create(Path::new(&path)).unwrap_or_else(|error| {
+            warn!("could not open event log {:?}: {}", path, error);
+            EventLog::default()
+        });
+    }
+
+    /// Les sessions suivantes sont ajoutées à la fin du fichier
+    pub fn create(path: &Path) -> Result<EventLog, String> {
+        let file = OpenOptions::new().create(true).append(true).open(path).map_err(|error| error.to_string())?;
+        info!("writing game events to {}", path.display());
+        return Ok(EventLog { file: Some(LineWriter::new(file)) });
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        return self.file.is_some();
+    }
+
+    fn write(&mut self, line: &LogLine) {
+        let Some(file) = self.file.as_mut() else { return; };
+        let json = serde_json::to_string(line).unwrap();
+        if let Err(error) = writeln!(file, "{}", json) {
+            warn!("could not write event log, closing it: {}", error);
+            self.file = None;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn log_game_events(
+    mut action_event: EventReader<PlayerActionEvent>,
+    mut level_end_event: EventReader<LevelEndEvent>,
+    mut change_level_event: EventReader<ChangeLevelEvent>,
+    mut event_log: ResMut<EventLog>,
+    level_res: Res<CurrentLevel>,
+    score: Res<Score>,
+    time: Res<Time<Real>>,
+    player: Query<&Player>,
+    // la fin est renvoyée tant que le niveau n'est pas reconstruit
+    mut level_ended: Local<bool>,
+) {
+    if change_level_event.read().last().is_some() {
+        *level_ended = false;
+    }
+    let Ok(player) = player.get_single() else { return; };
+    let (x, y) = (player.game_x.unwrap_or(-1), player.game_y.unwrap_or(-1));
+
+    let mut events = Vec::new();
+    for event in action_event.read() {
+        debug!("{:?} to {} {}, {} moves", event.action, x, y, score.moves);
+        events.push(LoggedEvent::Move { action: event.action, moves: score.moves, x, y });
+    }
+    if let Some(event) = level_end_event.read().last() {
+        if !*level_ended {
+            *level_ended = true;
+            events.push(match event.kind {
+                LevelEnd::Died => {
+                    info!("level {}: died at {} {} after {} moves", level_res.level, x, y, score.moves);
+                    LoggedEvent::Death { moves: score.moves, x, y }
+                }
+                LevelEnd::Completed => {
+                    info!("level {}: completed in {} moves, {:.1}s", level_res.level, score.moves, score.time);
+                    LoggedEvent::Completion { moves: score.moves, time: score.time }
+                }
+            });
+        }
+    }
+
+    if !event_log.is_enabled() { return; }
+    for event in events {
+        event_log.write(&LogLine { time: time.elapsed_seconds(), level: level_res.level, event });
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{app::{App, Plugin, Update}, ecs::{event::EventReader, schedule::{common_conditions::in_state, IntoSystemConfigs}, system::{Query, Res, ResMut, Resource}}};
+
+use crate::*;
+
+/// Luminosité d'une case déjà vue mais hors de vue du joueur
+pub const EXPLORED_LIGHT: f32 = 0.35;
+
+pub struct FogPlugin;
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<FogOfWar>()
+            .add_systems(Update, (
+                update_fog.before(compute_visibility_mask),
+                save_explored_cells.before(change_level_event_listener),
+            ).run_if(in_state(GameState::Game)));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CellMemory {
+    Unseen,
+    Explored,
+    Visible,
+}
+
+/// Cases vues par le joueur, gardées pour chaque niveau.
+#[derive(Resource, Default)]
+pub struct FogOfWar {
+    explored: HashMap<usize, Vec<bool>>,
+    visible: Vec<bool>,
+    pub enabled: bool,
+}
+
+impl FogOfWar {
+    pub fn cell(&self, level: usize, game_x: i32, game_y: i32) -> CellMemory {
+        let Some(index) = VisibilityMask::index(game_x, game_y) else { return CellMemory::Unseen; };
+        if self.visible.get(index).copied().unwrap_or(false) {
+            return CellMemory::Visible;
+        }
+        if self.explored.get(&level).is_some_and(|explored| explored[index]) {
+            return CellMemory::Explored;
+        }
+        return CellMemory::Unseen;
+    }
+
+    pub fn light(&self, level: usize, game_x: i32, game_y: i32) -> f32 {
+        match self.cell(level, game_x, game_y) {
+            CellMemory::Visible => 1.,
+            CellMemory::Explored => EXPLORED_LIGHT,
+            CellMemory::Unseen => 0.,
+        }
+    }
+
+    fn explored_mut(&mut self, level: usize, save: &SaveGame) -> &mut Vec<bool> {
+        return self.explored.entry(level).or_insert_with(|| {
+            let mut explored = vec![false; VisibilityMask::len()];
+            if let Some(saved) = save.data.explored.get(&level) {
+                for (cell, bit) in explored.iter_mut().zip(saved.chars()) {
+                    *cell = bit == '1';
+                }
+            }
+            explored
+        });
+    }
+}
+
+/// Vrai si aucun mur ne se trouve entre les deux cases (tracé de Bresenham).
+pub fn line_of_sight(walls: &HashSet<(i32, i32)>, from: (i32, i32), to: (i32, i32)) -> bool {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let step_x = if x < to.0 {1} else {-1};
+    let step_y = if y < to.1 {1} else {-1};
+    let mut error = dx + dy;
+
+    while (x, y) != to {
+        if (x, y) != from && walls.contains(&(x, y)) {
+            return false;
+        }
+        let double_error = 2 * error;
+        if double_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if double_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+    return true;
+}
+
+fn update_fog(
+    mut fog: ResMut<FogOfWar>,
+    level_res: Res<CurrentLevel>,
+    level_maps: Res<LevelMaps>,
+    level_assets: Res<Assets<LevelAsset>>,
+    save: Res<SaveGame>,
+    player: Query<&Player>,
+    wall_query: Query<&Wall>,
+) {
+    fog.enabled = level_maps.current(&level_res, &level_assets).is_some_and(|level| level.fog);
+    if !fog.enabled { return; }
+
+    let player = player.single();
+    let (Some(player_x), Some(player_y)) = (player.game_x, player.game_y) else { return; };
+
+    let walls: HashSet<(i32, i32)> = wall_query.iter().map(|wall| (wall.game_x, wall.game_y)).collect();
+    let mut visible = vec![false; VisibilityMask::len()];
+    for game_y in MASK_BOTTOM..SCREEN_GAME_Y {
+        for game_x in 0..SCREEN_GAME_X {
+            if line_of_sight(&walls, (player_x, player_y), (game_x, game_y)) {
+                visible[VisibilityMask::index(game_x, game_y).unwrap()] = true;
+            }
+        }
+    }
+
+    let explored = fog.explored_mut(level_res.level, &save);
+    for (cell, is_visible) in explored.iter_mut().zip(visible.iter()) {
+        *cell |= *is_visible;
+    }
+    fog.visible = visible;
+}
+
+fn save_explored_cells(
+    mut change_level_event: EventReader<ChangeLevelEvent>,
+    fog: Res<FogOfWar>,
+    level_res: Res<CurrentLevel>,
+    mut save: ResMut<SaveGame>,
+) {
+    if change_level_event.read().last().is_none() { return; }
+    let Some(explored) = fog.explored.get(&level_res.level) else { return; };
+
+    let bits: String = explored.iter().map(|cell| if *cell {'1'} else {'0'}).collect();
+    save.data.explored.insert(level_res.level, bits);
+    save.write();
+}
@@ -0,0 +1,103 @@
+use bevy::{app::{App, Plugin, PreUpdate, Update}, ecs::{event::EventReader, schedule::IntoSystemConfigs, system::{Res, ResMut, Resource}}, input::{gamepad::{Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, GamepadConnection, GamepadConnectionEvent, Gamepads}, Axis, Input, InputSystem}};
+
+use crate::*;
+
+/// En dessous, le stick est considéré au repos
+const STICK_DEAD_ZONE: f32 = 0.5;
+
+pub struct GamepadPlugin;
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ActiveGamepad>()
+            .init_resource::<GamepadActions>()
+            .add_systems(PreUpdate, (
+                track_gamepads,
+                read_gamepad,
+            ).chain().after(InputSystem))
+            .add_systems(Update, log_gamepad_changes);
+    }
+}
+
+/// Manette utilisée par le joueur, la dernière branchée
+#[derive(Resource, Default)]
+pub struct ActiveGamepad {
+    pub gamepad: Option<Gamepad>,
+    pub name: Option<String>,
+}
+
+/// Croix et stick de la manette pour cette image, les autres boutons passent par `Controls`
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct GamepadActions {
+    /// Maintenus, comme les flèches du clavier
+    pub left: bool,
+    pub right: bool,
+    pub up: bool,
+    pub down: bool,
+    /// Appuyés pendant cette image, pour les menus
+    pub confirm: bool,
+    pub back: bool,
+    pub up_pressed: bool,
+    pub down_pressed: bool,
+    pub left_pressed: bool,
+    pub right_pressed: bool,
+}
+
+fn track_gamepads(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+    mut active: ResMut<ActiveGamepad>,
+) {
+    for event in connection_events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                active.gamepad = Some(event.gamepad);
+                active.name = Some(info.name.clone());
+            }
+            GamepadConnection::Disconnected => {
+                if active.gamepad == Some(event.gamepad) {
+                    // reprendre une autre manette encore branchée
+                    active.gamepad = gamepads.iter().find(|gamepad| *gamepad != event.gamepad);
+                    active.name = active.gamepad.and_then(|gamepad| gamepads.name(gamepad)).map(|name| name.to_owned());
+                }
+            }
+        }
+    }
+}
+
+pub fn read_gamepad(
+    active: Res<ActiveGamepad>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut actions: ResMut<GamepadActions>,
+) {
+    let Some(gamepad) = active.gamepad else {
+        *actions = GamepadActions::default();
+        return;
+    };
+    let button = |button_type| GamepadButton::new(gamepad, button_type);
+    let stick_x = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)).unwrap_or(0.);
+    let stick_y = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.);
+
+    let previous = *actions;
+    actions.left = buttons.pressed(button(GamepadButtonType::DPadLeft)) || stick_x < -STICK_DEAD_ZONE;
+    actions.right = buttons.pressed(button(GamepadButtonType::DPadRight)) || stick_x > STICK_DEAD_ZONE;
+    actions.up = buttons.pressed(button(GamepadButtonType::DPadUp)) || stick_y > STICK_DEAD_ZONE;
+    actions.down = buttons.pressed(button(GamepadButtonType::DPadDown)) || stick_y < -STICK_DEAD_ZONE;
+
+    actions.confirm = buttons.just_pressed(button(GamepadButtonType::South));
+    actions.back = buttons.just_pressed(button(GamepadButtonType::East));
+    // le stick n'a pas de `just_pressed`, on compare avec l'image précédente
+    actions.up_pressed = actions.up && !previous.up;
+    actions.down_pressed = actions.down && !previous.down;
+    actions.left_pressed = actions.left && !previous.left;
+    actions.right_pressed = actions.right && !previous.right;
+}
+
+fn log_gamepad_changes(active: Res<ActiveGamepad>) {
+    if !active.is_changed() { return; }
+    match &active.name {
+        Some(name) => info!("gamepad in use: {}", name),
+        None => info!("no gamepad connected"),
+    }
+}
@@ -0,0 +1,172 @@
+use bevy::{app::{App, Plugin, PreUpdate}, ecs::{event::{Event, EventWriter}, query::With, schedule::IntoSystemConfigs, system::{Query, Res, ResMut, Resource}}, input::{mouse::MouseButton, touch::Touches, Input, InputSystem}, math::Vec2, time::{Real, Time}, utils::HashMap, window::{PrimaryWindow, Window}};
+
+use crate::*;
+
+/// Les seuils sont des fractions du plus petit côté de la fenêtre, pour ne pas dépendre de la densité de l'écran
+const SWIPE_FRACTION: f32 = 0.08;
+const SLOP_FRACTION: f32 = 0.03;
+/// Secondes sans bouger avant un appui long
+const LONG_PRESS_TIME: f32 = 0.5;
+/// Durée maximale d'une tape à deux doigts
+const TWO_FINGER_TAP_TIME: f32 = 0.35;
+/// La souris est suivie comme un doigt de plus
+const MOUSE_POINTER: u64 = u64::MAX;
+
+pub struct GesturePlugin;
+impl Plugin for GesturePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<GestureTracker>()
+            .add_event::<GestureEvent>()
+            .add_systems(PreUpdate, recognize_gestures.after(InputSystem));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Gesture {
+    Tap,
+    SwipeLeft,
+    SwipeRight,
+    SwipeUp,
+    SwipeDown,
+    LongPress,
+    TwoFingerTap,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct GestureEvent {
+    pub gesture: Gesture,
+}
+
+struct Pointer {
+    start: Vec2,
+    position: Vec2,
+    started_at: f32,
+    swipe: Option<Gesture>,
+    long_pressed: bool,
+}
+
+/// Plusieurs doigts posés en même temps, tape à deux doigts si personne ne bouge
+struct MultiTouch {
+    started_at: f32,
+    moved: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct GestureTracker {
+    pointers: HashMap<u64, Pointer>,
+    multi: Option<MultiTouch>,
+    /// Dernière position connue, si le bouton est relâché hors de la fenêtre
+    last_cursor: Option<Vec2>,
+}
+
+impl GestureTracker {
+    /// Glissement horizontal d'un doigt toujours posé : le joueur continue d'avancer
+    pub fn held(&self) -> Option<Gesture> {
+        if self.multi.is_some() { return None; }
+        return self.pointers.values()
+            .filter_map(|pointer| pointer.swipe)
+            .find(|swipe| matches!(swipe, Gesture::SwipeLeft | Gesture::SwipeRight));
+    }
+
+    fn press(&mut self, id: u64, position: Vec2, now: f32) {
+        self.pointers.insert(id, Pointer { start: position, position, started_at: now, swipe: None, long_pressed: false });
+        if self.pointers.len() >= 2 && self.multi.is_none() {
+            self.multi = Some(MultiTouch { started_at: now, moved: false });
+        }
+    }
+
+    fn release(&mut self, id: u64, now: f32, swipe_distance: f32, gestures: &mut EventWriter<GestureEvent>) {
+        let Some(pointer) = self.pointers.remove(&id) else { return; };
+
+        if let Some(multi) = &self.multi {
+            // la tape est reconnue quand le dernier doigt se lève
+            if self.pointers.is_empty() {
+                if !multi.moved && now - multi.started_at <= TWO_FINGER_TAP_TIME {
+                    gestures.send(GestureEvent { gesture: Gesture::TwoFingerTap });
+                }
+                self.multi = None;
+            }
+            return;
+        }
+
+        if pointer.swipe.is_none() && !pointer.long_pressed && pointer.start.distance(pointer.position) < swipe_distance {
+            gestures.send(GestureEvent { gesture: Gesture::Tap });
+        }
+    }
+}
+
+fn swipe_direction(delta: Vec2) -> Gesture {
+    if delta.x.abs() >= delta.y.abs() {
+        return if delta.x < 0. { Gesture::SwipeLeft } else { Gesture::SwipeRight };
+    }
+    // l'axe Y de la fenêtre va vers le bas
+    return if delta.y < 0. { Gesture::SwipeUp } else { Gesture::SwipeDown };
+}
+
+pub fn recognize_gestures(
+    time: Res<Time<Real>>,
+    touches: Res<Touches>,
+    mouse: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut tracker: ResMut<GestureTracker>,
+    mut gestures: EventWriter<GestureEvent>,
+) {
+    let Ok(window) = windows.get_single() else { return; };
+    let scale = window.width().min(window.height());
+    let swipe_distance = scale * SWIPE_FRACTION;
+    let slop = scale * SLOP_FRACTION;
+    let now = time.elapsed_seconds();
+
+    // écran tactile
+    for touch in touches.iter_just_pressed() {
+        tracker.press(touch.id(), touch.position(), now);
+    }
+    for touch in touches.iter() {
+        if let Some(pointer) = tracker.pointers.get_mut(&touch.id()) {
+            pointer.position = touch.position();
+        }
+    }
+
+    // souris
+    if let Some(cursor) = window.cursor_position() {
+        tracker.last_cursor = Some(cursor);
+    }
+    if let Some(cursor) = tracker.last_cursor {
+        if mouse.just_pressed(MouseButton::Left) {
+            tracker.press(MOUSE_POINTER, cursor, now);
+        }
+        if let Some(pointer) = tracker.pointers.get_mut(&MOUSE_POINTER) {
+            pointer.position = cursor;
+        }
+    }
+
+    let tracker = tracker.as_mut();
+    for pointer in tracker.pointers.values_mut() {
+        let delta = pointer.position - pointer.start;
+        if let Some(multi) = tracker.multi.as_mut() {
+            if delta.length() > slop {
+                multi.moved = true;
+            }
+            continue;
+        }
+        if pointer.swipe.is_none() && !pointer.long_pressed {
+            if delta.length() >= swipe_distance {
+                let swipe = swipe_direction(delta);
+                pointer.swipe = Some(swipe);
+                gestures.send(GestureEvent { gesture: swipe });
+            } else if delta.length() <= slop && now - pointer.started_at >= LONG_PRESS_TIME {
+                pointer.long_pressed = true;
+                gestures.send(GestureEvent { gesture: Gesture::LongPress });
+            }
+        }
+    }
+
+    let released: Vec<u64> = touches.iter_just_released().chain(touches.iter_just_canceled()).map(|touch| touch.id()).collect();
+    for id in released {
+        tracker.release(id, now, swipe_distance, &mut gestures);
+    }
+    if mouse.just_released(MouseButton::Left) {
+        tracker.release(MOUSE_POINTER, now, swipe_distance, &mut gestures);
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::{app::{App, Plugin, Update}, ecs::{schedule::{common_conditions::in_state, IntoSystemConfigs, OnEnter}, system::{Commands, Query, Res}}, hierarchy::BuildChildren, render::color::Color, sprite::Sprite, text::{Text, TextStyle}, ui::{node_bundles::{NodeBundle, TextBundle}, PositionType, Style, UiRect, Val}};
+
+use crate::*;
+
+const LOCKED_DOOR_COLOR: Color = Color::rgb(0.45, 0.45, 0.45);
+
+pub struct HudPlugin;
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::Game), spawn_hud)
+            .add_systems(Update, (
+                update_hud,
+                update_red_door,
+            ).run_if(in_state(GameState::Game)));
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum HudText {
+    Level,
+    Moves,
+    Time,
+    Chests,
+    Score,
+    /// Touches du clavier ou boutons de la manette branchée
+    Prompts,
+}
+
+fn spawn_hud(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                left: Val::Px(10.),
+                padding: UiRect::all(Val::Px(8.)),
+                column_gap: Val::Px(24.),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.5).into(),
+            ..default()
+        },
+        InGame,
+    )).with_children(|parent| {
+        for hud_text in [HudText::Level, HudText::Moves, HudText::Time, HudText::Chests, HudText::Score] {
+            parent.spawn((
+                TextBundle::from_section("", TextStyle {
+                    font_size: 24.,
+                    color: Color::WHITE,
+                    ..default()
+                }),
+                hud_text,
+            ));
+        }
+    });
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.),
+                left: Val::Px(10.),
+                padding: UiRect::all(Val::Px(6.)),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.5).into(),
+            ..default()
+        },
+        InGame,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 18.,
+                color: Color::WHITE,
+                ..default()
+            }),
+            HudText::Prompts,
+        ));
+    });
+}
+
+fn prompts(active_gamepad: &ActiveGamepad, bindings: &Bindings) -> String {
+    if active_gamepad.gamepad.is_some() {
+        return format!(
+            "Croix : bouger   {} : porte / coffre   {} : annuler   {} : recommencer   {} : pause",
+            bindings.buttons_label(Action::Use),
+            bindings.buttons_label(Action::Undo),
+            bindings.buttons_label(Action::Restart),
+            bindings.buttons_label(Action::Pause),
+        );
+    }
+    return format!(
+        "{} {} : bouger   {} : porte / coffre   {} : annuler   {} : recommencer   {} : pause",
+        bindings.keys_label(Action::MoveLeft),
+        bindings.keys_label(Action::MoveRight),
+        bindings.keys_label(Action::Use),
+        bindings.keys_label(Action::Undo),
+        bindings.keys_label(Action::Restart),
+        bindings.keys_label(Action::Pause),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_hud(
+    level_res: Res<CurrentLevel>,
+    level_maps: Res<LevelMaps>,
+    level_assets: Res<Assets<LevelAsset>>,
+    score: Res<Score>,
+    active_gamepad: Res<ActiveGamepad>,
+    controls: Res<Controls>,
+    chest_query: Query<&Chest>,
+    mut text_query: Query<(&HudText, &mut Text)>,
+) {
+    let level_name = level_maps.current(&level_res, &level_assets).and_then(|level| level.name.clone());
+    let open_chests = chest_query.iter().filter(|chest| chest.is_open).count();
+
+    for (hud_text, mut text) in text_query.iter_mut() {
+        text.sections[0].value = match hud_text {
+            HudText::Level => match &level_name {
+                Some(name) => format!("Niveau {} - {}", level_res.level, name),
+                None => format!("Niveau {}", level_res.level),
+            },
+            HudText::Moves => format!("Coups : {}", score.moves),
+            HudText::Time => format!("Temps : {:.1} s", score.time),
+            HudText::Chests => format!("Coffres : {}/{}", open_chests, chest_query.iter().len()),
+            HudText::Score => format!("Score : {}", score.total + score.level),
+            HudText::Prompts => prompts(&active_gamepad, &controls.bindings),
+        };
+    }
+}
+
+/// La porte rouge reste grisée tant qu'il reste des coffres fermés
+fn update_red_door(
+    chest_query: Query<&Chest>,
+    mut red_door_query: Query<&mut Sprite, With<RedDoor>>,
+) {
+    let all_chest_open = chest_query.iter().all(|chest| chest.is_open);
+    for mut sprite in red_door_query.iter_mut() {
+        sprite.color = if all_chest_open { Color::WHITE } else { LOCKED_DOOR_COLOR };
+    }
+}
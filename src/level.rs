@@ -1,5 +1,5 @@
 use bevy::{ecs::{event::{Event, EventReader}, system::{Commands, ResMut, Res, Query}, entity::Entity, query::With}, asset::{AssetServer, LoadContext, AssetLoader, io::Reader, AsyncReadExt}, transform::components::Transform, utils::BoxedFuture};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use bevy::utils::thiserror;
 
 use thiserror::Error;
@@ -12,9 +12,10 @@ impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(OnEnter(GameState::Game), send_maps_on_load)
-            .add_systems(Update, change_level_event_listener.run_if(in_state(GameState::Game)))
+            // l'ancien niveau disparaît avant le tour : un coffre détruit ne libère plus de chauve-souris
+            .add_systems(Update, (change_level_event_listener, apply_deferred).chain().before(TurnSet::Input).run_if(in_state(GameState::Game)))
             .init_resource::<LevelMaps>()
-            .insert_resource(CurrentLevel { level: 0 })
+            .insert_resource(CurrentLevel { level: 1 })
             .init_asset::<LevelAsset>()
             .init_asset_loader::<LevelAssetLoader>()
             .add_event::<ChangeLevelEvent>();
@@ -31,12 +32,15 @@ pub struct ChangeLevelEvent {
     pub new_level: bool,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn change_level_event_listener(
     mut change_level_event: EventReader<ChangeLevelEvent>,
     mut level_res: ResMut<CurrentLevel>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    custom_assets: ResMut<Assets<LevelAsset>>,
+    level_maps: Res<LevelMaps>,
+    custom_assets: Res<Assets<LevelAsset>>,
+    textures_ressource: Res<TexturesRessource>,
     mut player_query: Query<(&mut Transform, &mut Player)>,
 
     mut despawn_blue_door_query: Query<Entity, With<BlueDoor>>,
@@ -44,6 +48,8 @@ pub fn change_level_event_listener(
     mut despawn_wall_query: Query<Entity, With<Wall>>,
     mut despawn_chest_query: Query<Entity, With<Chest>>,
     mut despawn_monster_query: Query<Entity, With<Monster>>,
+    mut despawn_coin_query: Query<Entity, With<Coin>>,
+    mut despawn_torch_query: Query<Entity, With<Torch>>,
 
     mut game_state: ResMut<NextState<GameState>>,
 ) {
@@ -60,6 +66,7 @@ pub fn change_level_event_listener(
         }
     }
     let current_level = level_res.level;
+    let _span = info_span!("level_load", level = current_level).entered();
 
     // destroy ancient level
     {
@@ -78,6 +85,12 @@ pub fn change_level_event_listener(
         if !despawn_monster_query.is_empty() {
             for entity in &mut despawn_monster_query { commands.entity(entity).despawn(); }
         }
+        if !despawn_coin_query.is_empty() {
+            for entity in &mut despawn_coin_query { commands.entity(entity).despawn(); }
+        }
+        if !despawn_torch_query.is_empty() {
+            for entity in &mut despawn_torch_query { commands.entity(entity).despawn(); }
+        }
     }
 
     // build new level
@@ -85,15 +98,14 @@ pub fn change_level_event_listener(
         let wall_tex = asset_server.load("textures/walls/dungeon-wall.png");
         let blue_door_tex = asset_server.load("textures/walls/door-blue.png");
         let red_door_tex = asset_server.load("textures/walls/door-red.png");
-        let chest_tex = asset_server.load("textures/object/chest-1.png");
+        let coin_tex = asset_server.load("textures/object/yellow.png");
 
-        let level_map = {
-            let handle: Handle<LevelAsset> = asset_server.load(format!("map/level-{}.lev", current_level));
-            let custom_asset = custom_assets.get(&handle);
-            let level_map = &custom_asset.unwrap().map;
-            let level_map: Vec<&str> = level_map.split_whitespace().collect();
-            level_map
+        let Some(level) = level_maps.current(&level_res, &custom_assets) else {
+            warn!("level {} is not loaded", current_level);
+            return;
         };
+        info!("building level {}", level.name.as_deref().unwrap_or("without a name"));
+        let level_map: Vec<&str> = level.map.split_whitespace().collect();
 
         let mut game_x;
         let mut game_y = level_map.len() as i32 -1;
@@ -122,6 +134,7 @@ pub fn change_level_event_listener(
                                 ..default()
                             },
                             Wall { game_x, game_y },
+                            InGame,
                         ));
                         { // murs tout en bas
                             if index == 12 {
@@ -142,6 +155,7 @@ pub fn change_level_event_listener(
                                             ..default()
                                         },
                                         Wall { game_x, game_y: game_y-i, },
+                                        InGame,
                                     ));
                                 }
                             }
@@ -169,6 +183,7 @@ pub fn change_level_event_listener(
                                 ..default()
                             },
                             BlueDoor { game_x, game_y },
+                            InGame,
                         ));
                     }
                     'R' => {
@@ -187,18 +202,19 @@ pub fn change_level_event_listener(
                                 ..default()
                             },
                             RedDoor { game_x, game_y },
+                            InGame,
                         ));
                     }
                     'C' => {
                         let block_pos = Vec2::new(block_pos.x, block_pos.y-5.);
                         commands.spawn((
-                            SpriteBundle {
-                                texture: chest_tex.clone(),
+                            SpriteSheetBundle {
+                                texture_atlas: textures_ressource.chest.clone(),
                                 transform: Transform {
                                     translation: block_pos.extend(0.),
                                     ..default()
                                 },
-                                sprite: Sprite {
+                                sprite: TextureAtlasSprite {
                                     color: Color::rgb(1., 1., 1.),
                                     custom_size: Some(Vec2::new(50., 50.,)),
                                     ..default()
@@ -206,6 +222,31 @@ pub fn change_level_event_listener(
                                 ..default()
                             },
                             Chest::new(game_x, game_y),
+                            chest_animation(),
+                            InGame,
+                        ));
+                    }
+                    'o' => {
+                        spawn_coin(&mut commands, coin_tex.clone(), game_x, game_y);
+                    }
+                    'T' => {
+                        commands.spawn((
+                            SpriteSheetBundle {
+                                texture_atlas: textures_ressource.torch.clone(),
+                                transform: Transform {
+                                    translation: block_pos.extend(0.),
+                                    ..default()
+                                },
+                                sprite: TextureAtlasSprite {
+                                    color: Color::rgb(1., 1., 1.),
+                                    custom_size: Some(Vec2::new(25., 48.,)),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            Torch::new(game_x, game_y),
+                            torch_animation(),
+                            InGame,
                         ));
                     }
                     _ => (),
@@ -223,24 +264,119 @@ pub fn send_maps_on_load(mut level_maps: ResMut<LevelMaps>, custom_assets: ResMu
     for map_handle in &level_maps.maps_handle {
         match custom_assets.get(map_handle) {
             Some(v) => maps.push(v.clone()),
-            None => return
+            None => {
+                // l'écran de chargement attend normalement toutes les cartes
+                warn!("level map {:?} is not loaded", map_handle.path());
+                return;
+            }
         }
     }
 
-    change_level_event.send(ChangeLevelEvent {new_level:true});
+    // les copies mises à jour par `reload_modified_level` restent valables d'une partie à l'autre
+    if level_maps.maps.is_empty() {
+        level_maps.maps = maps;
+    }
+
+    change_level_event.send(ChangeLevelEvent {new_level:false});
     level_maps.sended = true;
 }
 
+/// Aussi utilisé pour remettre une pièce en annulant un coup
+#[allow(clippy::redundant_field_names)]
+pub fn spawn_coin(commands: &mut Commands, texture: Handle<Image>, game_x: i32, game_y: i32) {
+    let block_pos = vec2(25. +(game_x*50-RIGHT) as f32, 25. + (game_y*50-TOP) as f32);
+    commands.spawn((
+        SpriteBundle {
+            texture: texture,
+            transform: Transform {
+                translation: block_pos.extend(0.),
+                ..default()
+            },
+            sprite: Sprite {
+                color: Color::rgb(1., 1., 1.),
+                custom_size: Some(Vec2::new(30., 30.,)),
+                ..default()
+            },
+            ..default()
+        },
+        Coin { game_x, game_y },
+        InGame,
+    ));
+}
+
 #[derive(Resource, Default)]
 pub struct LevelMaps {
     pub maps_handle: Vec<Handle<LevelAsset>>,
+    /// Dernière version valide de chaque carte, une carte modifiée invalide ne la remplace pas
     pub maps: Vec<LevelAsset>,
     pub sended: bool,
 }
 
-#[derive(Asset, TypePath, Debug, Deserialize, Clone)]
+impl LevelMaps {
+    /// La copie valide, ou l'asset chargé tant que les copies ne sont pas faites
+    pub fn current<'a>(&'a self, level_res: &CurrentLevel, level_assets: &'a Assets<LevelAsset>) -> Option<&'a LevelAsset> {
+        if level_res.level == 0 { return None; }
+        if let Some(level) = self.maps.get(level_res.level-1) {
+            return Some(level);
+        }
+        return self.maps_handle.get(level_res.level-1).and_then(|handle| level_assets.get(handle));
+    }
+}
+
+#[derive(Asset, TypePath, Debug, Serialize, Deserialize, Clone)]
 pub struct LevelAsset {
     pub map: String,
+    /// Nombre de mouvements à ne pas dépasser pour obtenir le bonus
+    #[serde(default)]
+    pub par: Option<u32>,
+    /// Seules les cases proches des torches et du joueur sont visibles
+    #[serde(default)]
+    pub dark: bool,
+    /// Brouillard de guerre : les cases jamais vues sont cachées
+    #[serde(default)]
+    pub fog: bool,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Nom de la musique dans `assets/audio/music`
+    #[serde(default)]
+    pub music: Option<String>,
+    /// Impose le tour par tour ou le temps réel, sinon le choix du joueur
+    #[serde(default)]
+    pub mode: Option<TickMode>,
+}
+
+impl LevelAsset {
+    /// Les lignes `clé=valeur` sont des propriétés du niveau, les autres forment la carte.
+    pub fn parse(text: &str) -> LevelAsset {
+        let mut map = String::new();
+        let mut par = None;
+        let mut dark = false;
+        let mut fog = false;
+        let mut name = None;
+        let mut music = None;
+        let mut mode = None;
+
+        for line in text.lines() {
+            let line = line.trim();
+            match line.split_once('=') {
+                Some((key, value)) => match key.trim() {
+                    "par" => par = value.trim().parse().ok(),
+                    "dark" => dark = value.trim() == "true",
+                    "fog" => fog = value.trim() == "true",
+                    "name" => name = Some(value.trim().to_owned()),
+                    "music" => music = Some(value.trim().to_owned()),
+                    "mode" => mode = TickMode::parse(value.trim()),
+                    _ => (),
+                },
+                None => {
+                    map.push_str(line);
+                    map.push('\n');
+                }
+            }
+        }
+
+        return LevelAsset { map, par, dark, fog, name, music, mode };
+    }
 }
 
 #[derive(Default)]
@@ -269,7 +405,7 @@ impl AssetLoader for LevelAssetLoader {
             let mut bytes = Vec::new();
             reader.read_to_end(&mut bytes).await?;
             let map = String::from_utf8(bytes).unwrap();
-            let custom_asset = LevelAsset { map };
+            let custom_asset = LevelAsset::parse(&map);
             Ok(custom_asset)
         })
     }
@@ -0,0 +1,266 @@
+// les fonctions du jeu finissent par un `return` explicite
+#![allow(clippy::needless_return)]
+
+use bevy::{math::*, prelude::*};
+use bevy_pixel_camera::PixelCameraPlugin;
+
+mod level;
+pub use crate::level::*;
+mod simple_entities;
+pub use crate::simple_entities::*;
+mod monster;
+pub use crate::monster::Monster;
+mod player;
+pub use crate::player::*;
+mod tick;
+pub use crate::tick::*;
+mod end_game;
+pub use crate::end_game::*;
+mod math;
+pub use crate::math::*;
+mod setup;
+pub use crate::setup::*;
+mod save;
+pub use crate::save::*;
+mod score;
+pub use crate::score::*;
+mod lighting;
+pub use crate::lighting::*;
+mod fog;
+pub use crate::fog::*;
+mod menu;
+pub use crate::menu::*;
+mod pause;
+pub use crate::pause::*;
+mod hud;
+pub use crate::hud::*;
+mod loading;
+pub use crate::loading::*;
+mod transition;
+pub use crate::transition::*;
+mod sound;
+pub use crate::sound::*;
+mod animation;
+pub use crate::animation::*;
+mod gamepad;
+pub use crate::gamepad::*;
+mod undo;
+pub use crate::undo::*;
+mod controls;
+pub use crate::controls::*;
+mod gesture;
+pub use crate::gesture::*;
+mod queue;
+pub use crate::queue::*;
+mod realtime;
+pub use crate::realtime::*;
+mod sim;
+pub use crate::sim::*;
+mod reload;
+pub use crate::reload::*;
+mod event_log;
+pub use crate::event_log::*;
+#[cfg(feature = "dev")]
+mod console;
+#[cfg(feature = "dev")]
+pub use crate::console::*;
+#[cfg(feature = "dev")]
+mod overlay;
+#[cfg(feature = "dev")]
+pub use crate::overlay::*;
+
+// screen size
+const RIGHT: i32 = 450;
+const TOP: i32 = 300;
+
+// Game Size
+const SCREEN_GAME_X: i32 = 18;
+const SCREEN_GAME_Y: i32 = 12;
+
+
+/// Le jeu complet, avec sa fenêtre
+pub fn app() -> App {
+    let mut app = App::new();
+    app
+        .add_plugins(DefaultPlugins
+            .set(ImagePlugin::default_nearest())
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    fit_canvas_to_parent: true,
+                    prevent_default_event_handling: false,
+                    title: "Dungeon - Game of Thrones Adventure".to_owned(),
+                    ..default()
+                }),
+                ..default()
+            })
+        )
+        .add_plugins(PixelCameraPlugin)
+        .init_resource::<MovementSpeed>()
+        .add_plugins(SetupPlugin)
+        .add_plugins(PlayerPlugin)
+        .add_plugins(LevelPlugin)
+        .add_plugins(TickPlugin)
+        .add_plugins(EndGamePlugin)
+        .add_plugins(SavePlugin)
+        .add_plugins(ScorePlugin)
+        .add_plugins(LightingPlugin)
+        .add_plugins(FogPlugin)
+        .add_plugins(MenuPlugin)
+        .add_plugins(PausePlugin)
+        .add_plugins(HudPlugin)
+        .add_plugins(LoadingPlugin)
+        .add_plugins(TransitionPlugin)
+        .add_plugins(SoundPlugin)
+        .add_plugins(SpriteAnimationPlugin)
+        .add_plugins(GamepadPlugin)
+        .add_plugins(UndoPlugin)
+        .add_plugins(ControlsPlugin)
+        .add_plugins(GesturePlugin)
+        .add_plugins(InputQueuePlugin)
+        .add_plugins(RealtimePlugin)
+        .add_plugins(HotReloadPlugin)
+        .add_plugins(EventLogPlugin)
+        .insert_resource(ClearColor(Color::rgb(0.05, 0.05, 0.05)))
+        .init_resource::<TexturesRessource>()
+        .add_state::<GameState>()
+        .add_state::<PauseState>()
+        .add_systems(Update, (
+            animate_entity,
+        ).in_set(TurnSet::Animate).run_if(in_state(GameState::Game)).run_if(in_state(PauseState::Running)));
+    #[cfg(feature = "dev")]
+    app
+        .add_plugins(ConsolePlugin)
+        .add_plugins(DebugOverlayPlugin);
+    return app;
+}
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub enum GameState {
+    #[default]
+    Loading,
+    Menu,
+    Game,
+    End
+}
+
+/// La partie reste en `GameState::Game` pendant la pause pour ne pas reconstruire le niveau
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused
+}
+
+/// Vitesses des déplacements en pixels par seconde de temps virtuel :
+/// `Time<Virtual>::set_relative_speed` accélère tout le jeu, la pause l'arrête
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct MovementSpeed {
+    pub player: f32,
+    pub falling: f32,
+    pub monster: f32,
+}
+
+impl Default for MovementSpeed {
+    fn default() -> Self {
+        // anciennes valeurs en pixels par image, à 60 images par seconde
+        MovementSpeed { player: 90., falling: 120., monster: 90. }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Direction {
+    Left,
+    Right,
+    No,
+    Bottom
+}
+
+/// Atlas des sprites animés, découpés au démarrage
+#[derive(Resource, Default)]
+pub struct TexturesRessource {
+    pub player: Handle<TextureAtlas>,
+    pub bat: Handle<TextureAtlas>,
+    pub chest: Handle<TextureAtlas>,
+    pub torch: Handle<TextureAtlas>,
+
+    /// Images des atlas, à attendre sur l'écran de chargement
+    pub sheets: Vec<Handle<Image>>,
+}
+
+impl TexturesRessource {
+    pub fn handles(&self) -> Vec<Handle<Image>> {
+        return self.sheets.clone();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn animate_entity(
+    mut queries: ParamSet<(
+        Query<(&mut Transform, &mut Player, &mut SpriteAnimation)>,
+        Query<(&Chest, &mut SpriteAnimation)>,
+        Query<(&mut Transform, &mut Monster, &mut SpriteAnimation)>
+    )>,
+    mut end_tick_event: EventWriter<EndTickEvent>,
+    time: Res<Time>,
+    speed: Res<MovementSpeed>,
+) {
+    let delta = time.delta_seconds();
+
+    // Player
+    {
+        let mut player_query = queries.p0();
+
+        let player_query = player_query.single_mut();
+        let mut player_transform = player_query.0;
+        let mut player = player_query.1;
+        let mut player_animation = player_query.2;
+        if player.game_x.is_none() || player.game_y.is_none() { return; }
+
+        let result = player.animate(&player_transform.translation, speed.player * delta, speed.falling * delta);
+        player_transform.translation = result.0;
+        let end_tick = result.1;
+
+        if player.direction == Direction::Left {
+            player_animation.play("left");
+        } 
+        else if player.direction == Direction::Right {
+            player_animation.play("right");
+        }
+        else  {
+            player_animation.play("idle");
+        }
+
+        if end_tick {
+            end_tick_event.send(EndTickEvent);
+        }
+    }
+
+    // Chest
+    {
+        let mut chest_query = queries.p1();
+
+        for (chest, mut chest_animation) in chest_query.iter_mut() {
+            if chest.is_open {
+                chest_animation.play("open");
+            }
+        }
+    }
+
+    { // Monster
+        let mut monster_query = queries.p2();
+        for monster in monster_query.iter_mut() {
+            let mut monster_transform = monster.0;
+            let mut monster_entity = monster.1;
+            let mut monster_animation = monster.2;
+
+            monster_transform.translation = monster_entity.animate(&monster_transform.translation, speed.monster * delta);
+
+            if monster_entity.direction() == Direction::Left {
+                monster_animation.play("left");
+            } 
+            else { // Right is the default direction
+                monster_animation.play("right");
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::{app::{App, Plugin, Update}, ecs::{schedule::{common_conditions::in_state, IntoSystemConfigs, OnEnter}, system::{Commands, Query, Res, ResMut, Resource}}, math::Vec2, render::color::Color, sprite::{Sprite, SpriteBundle}, transform::components::Transform};
+
+use crate::*;
+
+const TORCH_RADIUS: f32 = 3.5;
+const PLAYER_RADIUS: f32 = 2.5;
+/// Largeur (en cases) du dégradé entre lumière et obscurité
+const LIGHT_FALLOFF: f32 = 1.5;
+/// Les murs du bas descendent sous la grille, l'obscurité doit les couvrir aussi
+pub const MASK_BOTTOM: i32 = -4;
+
+pub struct LightingPlugin;
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<VisibilityMask>()
+            .add_systems(OnEnter(GameState::Game), spawn_shade_cells)
+            .add_systems(Update, (
+                compute_visibility_mask,
+                apply_visibility_mask.after(compute_visibility_mask),
+            ).run_if(in_state(GameState::Game)));
+    }
+}
+
+/// Luminosité de chaque case, de 0 (noir) à 1 (visible).
+#[derive(Resource)]
+pub struct VisibilityMask {
+    cells: Vec<f32>,
+}
+
+impl Default for VisibilityMask {
+    fn default() -> Self {
+        VisibilityMask { cells: vec![1.; VisibilityMask::len()] }
+    }
+}
+
+impl VisibilityMask {
+    pub fn len() -> usize {
+        return (SCREEN_GAME_X * (SCREEN_GAME_Y - MASK_BOTTOM)) as usize;
+    }
+
+    pub fn index(game_x: i32, game_y: i32) -> Option<usize> {
+        if !(0..SCREEN_GAME_X).contains(&game_x) || !(MASK_BOTTOM..SCREEN_GAME_Y).contains(&game_y) { return None; }
+        return Some(((game_y - MASK_BOTTOM) * SCREEN_GAME_X + game_x) as usize);
+    }
+
+    pub fn get(&self, game_x: i32, game_y: i32) -> f32 {
+        return VisibilityMask::index(game_x, game_y).map_or(0., |index| self.cells[index]);
+    }
+
+    fn set(&mut self, game_x: i32, game_y: i32, light: f32) {
+        if let Some(index) = VisibilityMask::index(game_x, game_y) {
+            self.cells[index] = light;
+        }
+    }
+
+    fn fill(&mut self, light: f32) {
+        self.cells.iter_mut().for_each(|cell| *cell = light);
+    }
+}
+
+#[derive(Component)]
+pub struct ShadeCell {
+    game_x: i32,
+    game_y: i32,
+}
+
+fn spawn_shade_cells(mut commands: Commands) {
+    for game_y in MASK_BOTTOM..SCREEN_GAME_Y {
+        for game_x in 0..SCREEN_GAME_X {
+            commands.spawn((
+                SpriteBundle {
+                    transform: Transform {
+                        translation: vec3(25. + (game_x*50-RIGHT) as f32, 25. + (game_y*50-TOP) as f32, 3.),
+                        ..default()
+                    },
+                    sprite: Sprite {
+                        color: Color::rgba(0., 0., 0., 0.),
+                        custom_size: Some(Vec2::new(50., 50.)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ShadeCell { game_x, game_y },
+                InGame,
+            ));
+        }
+    }
+}
+
+fn light_from(source: (i32, i32), radius: f32, game_x: i32, game_y: i32) -> f32 {
+    let distance = Vec2::new((game_x - source.0) as f32, (game_y - source.1) as f32).length();
+    return ((radius - distance) / LIGHT_FALLOFF).clamp(0., 1.);
+}
+
+pub fn compute_visibility_mask(
+    mut mask: ResMut<VisibilityMask>,
+    level_res: Res<CurrentLevel>,
+    level_maps: Res<LevelMaps>,
+    level_assets: Res<Assets<LevelAsset>>,
+    fog: Res<FogOfWar>,
+    player: Query<&Player>,
+    torch_query: Query<&Torch>,
+) {
+    let is_dark = level_maps.current(&level_res, &level_assets).is_some_and(|level| level.dark);
+    if !is_dark && !fog.enabled {
+        mask.fill(1.);
+        return;
+    }
+
+    let mut sources: Vec<((i32, i32), f32)> = torch_query.iter().map(|torch| ((torch.game_x, torch.game_y), TORCH_RADIUS)).collect();
+    if let Ok(player) = player.get_single() {
+        if let (Some(game_x), Some(game_y)) = (player.game_x, player.game_y) {
+            sources.push(((game_x, game_y), PLAYER_RADIUS));
+        }
+    }
+
+    for game_y in MASK_BOTTOM..SCREEN_GAME_Y {
+        for game_x in 0..SCREEN_GAME_X {
+            let mut light = 1.;
+            if is_dark {
+                light = sources.iter()
+                    .map(|(source, radius)| light_from(*source, *radius, game_x, game_y))
+                    .fold(0., f32::max);
+            }
+            if fog.enabled {
+                light = f32::min(light, fog.light(level_res.level, game_x, game_y));
+            }
+            mask.set(game_x, game_y, light);
+        }
+    }
+}
+
+fn apply_visibility_mask(
+    mask: Res<VisibilityMask>,
+    mut shade_query: Query<(&ShadeCell, &mut Sprite)>,
+) {
+    for (cell, mut sprite) in shade_query.iter_mut() {
+        sprite.color.set_a(1. - mask.get(cell.game_x, cell.game_y));
+    }
+}
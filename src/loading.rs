@@ -0,0 +1,134 @@
+use bevy::{app::{App, Plugin, Startup, Update}, asset::{AssetServer, LoadState, UntypedHandle}, ecs::{entity::Entity, query::With, schedule::{common_conditions::in_state, IntoSystemConfigs, NextState, OnEnter, OnExit}, system::{Commands, Query, Res, ResMut, Resource}}, hierarchy::{BuildChildren, DespawnRecursiveExt}, log::{info, warn}, render::color::Color, text::TextStyle, ui::{node_bundles::{NodeBundle, TextBundle}, AlignItems, FlexDirection, JustifyContent, Style, Val}};
+
+use crate::*;
+
+/// Textures utilisées en jeu mais chargées au moment de construire le niveau
+const PRELOADED_TEXTURES: [&str; 8] = [
+    "textures/walls/dungeon-wall.png",
+    "textures/walls/door-blue.png",
+    "textures/walls/door-red.png",
+    "textures/object/yellow.png",
+    "textures/decor/cloud.png",
+    "textures/decor/G.png",
+    "textures/entity/hero1.png",
+    "textures/object/chest-4.png",
+];
+const BAR_WIDTH: f32 = 400.;
+
+pub struct LoadingPlugin;
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<LoadingAssets>()
+            .add_systems(Startup, collect_loading_assets.after(load_level_maps).after(load_entity_assets))
+            .add_systems(OnEnter(GameState::Loading), spawn_loading_screen)
+            .add_systems(Update, update_loading.run_if(in_state(GameState::Loading)))
+            .add_systems(OnExit(GameState::Loading), despawn_loading_screen);
+    }
+}
+
+/// Tout ce qui doit être chargé avant d'afficher le menu
+#[derive(Resource, Default)]
+pub struct LoadingAssets {
+    pub handles: Vec<UntypedHandle>,
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingBar;
+
+fn collect_loading_assets(
+    asset_server: Res<AssetServer>,
+    level_maps: Res<LevelMaps>,
+    textures_ressource: Res<TexturesRessource>,
+    mut loading_assets: ResMut<LoadingAssets>,
+) {
+    let handles = &mut loading_assets.handles;
+    handles.extend(level_maps.maps_handle.iter().map(|handle| handle.clone().untyped()));
+    handles.extend(textures_ressource.handles().into_iter().map(|handle| handle.untyped()));
+    handles.extend(PRELOADED_TEXTURES.iter().map(|path| asset_server.load::<Image>(*path).untyped()));
+}
+
+fn spawn_loading_screen(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(16.),
+                ..default()
+            },
+            ..default()
+        },
+        LoadingScreen,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("Chargement...", TextStyle {
+            font_size: 40.,
+            color: Color::WHITE,
+            ..default()
+        }));
+        parent.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(20.),
+                ..default()
+            },
+            background_color: Color::rgb(0.2, 0.2, 0.25).into(),
+            ..default()
+        }).with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(0.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.75, 0.55, 0.1).into(),
+                    ..default()
+                },
+                LoadingBar,
+            ));
+        });
+    });
+}
+
+fn update_loading(
+    asset_server: Res<AssetServer>,
+    loading_assets: Res<LoadingAssets>,
+    mut bar_query: Query<&mut Style, With<LoadingBar>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let mut done = 0;
+    for handle in &loading_assets.handles {
+        match asset_server.get_load_state(handle.id()) {
+            Some(LoadState::Loaded) => done += 1,
+            Some(LoadState::Failed) => {
+                // un fichier manquant ne doit pas bloquer le jeu sur l'écran de chargement
+                warn!("asset failed to load: {:?}", handle.path());
+                done += 1;
+            }
+            _ => (),
+        }
+    }
+
+    let total = loading_assets.handles.len().max(1);
+    for mut style in bar_query.iter_mut() {
+        style.width = Val::Px(BAR_WIDTH * done as f32 / total as f32);
+    }
+
+    if done >= loading_assets.handles.len() {
+        info!("{} assets loaded", done);
+        game_state.set(GameState::Menu);
+    }
+}
+
+fn despawn_loading_screen(mut commands: Commands, screen_query: Query<Entity, With<LoadingScreen>>) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
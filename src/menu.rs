@@ -0,0 +1,548 @@
+use bevy::{app::{App, AppExit, Plugin, Update}, ecs::{entity::Entity, event::{Event, EventReader, EventWriter}, query::{Changed, With}, schedule::{common_conditions::{in_state, resource_changed}, Condition, IntoSystemConfigs, NextState, OnEnter, OnExit, State}, system::{Commands, Query, Res, ResMut, Resource}}, hierarchy::{BuildChildren, DespawnRecursiveExt}, input::{keyboard::KeyCode, Input}, log::warn, render::color::Color, text::TextStyle, ui::{node_bundles::{ButtonBundle, NodeBundle, TextBundle}, AlignItems, BackgroundColor, FlexDirection, Interaction, JustifyContent, Style, UiRect, Val}, window::Window};
+
+use crate::*;
+
+const BUTTON_COLOR: Color = Color::rgb(0.2, 0.2, 0.25);
+const SELECTED_COLOR: Color = Color::rgb(0.75, 0.55, 0.1);
+const DISABLED_COLOR: Color = Color::rgb(0.1, 0.1, 0.1);
+const VOLUME_STEP: f32 = 0.1;
+
+pub struct MenuPlugin;
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<MenuPage>()
+            .init_resource::<MenuSelection>()
+            .add_event::<MenuEvent>()
+            .add_systems(OnEnter(GameState::Menu), (
+                despawn_game_world,
+                open_main_page,
+            ))
+            .add_systems(OnExit(GameState::Menu), despawn_menu)
+            .add_systems(OnExit(PauseState::Paused), despawn_menu)
+            .add_systems(OnEnter(GameState::End), open_end_page)
+            .add_systems(OnExit(GameState::End), despawn_menu)
+            .add_systems(Update, (
+                build_menu_page.run_if(resource_changed::<MenuPage>()),
+                capture_rebind,
+                menu_keyboard,
+                menu_pointer,
+                highlight_selection,
+                handle_menu_events,
+            ).chain().run_if(in_state(GameState::Menu).or_else(in_state(PauseState::Paused)).or_else(in_state(GameState::End))));
+    }
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
+pub enum MenuPage {
+    #[default]
+    Main,
+    LevelSelect,
+    Settings,
+    Controls,
+    Pause,
+    End,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub enum MenuButton {
+    NewGame,
+    Continue,
+    LevelSelect,
+    Settings,
+    Quit,
+    Level(usize),
+    MusicVolume,
+    SfxVolume,
+    Fullscreen,
+    InputBuffer,
+    TickMode,
+    Difficulty,
+    Controls,
+    Preset,
+    Rebind(Action),
+    Resume,
+    RestartLevel,
+    QuitToMenu,
+    PlayAgain,
+    ExportRun,
+    Back,
+}
+
+impl MenuButton {
+    /// Réglages qui se modifient avec gauche / droite
+    pub fn adjustable(&self) -> bool {
+        return matches!(self, MenuButton::MusicVolume | MenuButton::SfxVolume | MenuButton::Fullscreen | MenuButton::InputBuffer | MenuButton::TickMode | MenuButton::Difficulty | MenuButton::Preset);
+    }
+}
+
+/// Bouton choisi au clavier ou survolé par la souris
+#[derive(Resource, Default)]
+pub struct MenuSelection {
+    pub index: usize,
+}
+
+/// Un bouton activé, `step` vaut -1 ou 1 pour les réglages qui se modifient avec gauche / droite
+#[derive(Event, Clone, Copy)]
+pub struct MenuEvent {
+    pub button: MenuButton,
+    pub step: i32,
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component)]
+struct MenuItem {
+    index: usize,
+    enabled: bool,
+}
+
+fn despawn_game_world(
+    mut commands: Commands,
+    game_query: Query<Entity, With<InGame>>,
+    mut level_maps: ResMut<LevelMaps>,
+) {
+    for entity in game_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    // le niveau sera reconstruit en revenant dans la partie
+    level_maps.sended = false;
+}
+
+fn open_main_page(mut page: ResMut<MenuPage>) {
+    *page = MenuPage::Main;
+    page.set_changed();
+}
+
+pub(crate) fn open_pause_page(mut page: ResMut<MenuPage>, mut selection: ResMut<MenuSelection>) {
+    *page = MenuPage::Pause;
+    selection.index = 0;
+    page.set_changed();
+}
+
+fn open_end_page(mut page: ResMut<MenuPage>, mut selection: ResMut<MenuSelection>) {
+    *page = MenuPage::End;
+    selection.index = 0;
+    page.set_changed();
+}
+
+fn despawn_menu(mut commands: Commands, root_query: Query<Entity, With<MenuRoot>>) {
+    for entity in root_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn percent(volume: f32) -> u32 {
+    return (volume * 100.).round() as u32;
+}
+
+/// Texte, action et disponibilité de chaque bouton de la page
+fn page_items(page: MenuPage, save: &SaveGame, controls: &Controls, rebinding: &Rebinding) -> Vec<(String, MenuButton, bool)> {
+    let settings = &save.data.settings;
+    match page {
+        MenuPage::Main => {
+            let mut items = vec![
+                ("Nouvelle partie".to_owned(), MenuButton::NewGame, true),
+                (format!("Continuer (niveau {})", save.data.unlocked_level), MenuButton::Continue, true),
+                ("Choix du niveau".to_owned(), MenuButton::LevelSelect, true),
+                ("Paramètres".to_owned(), MenuButton::Settings, true),
+            ];
+            if cfg!(not(target_arch = "wasm32")) {
+                items.push(("Quitter".to_owned(), MenuButton::Quit, true));
+            }
+            items
+        }
+        MenuPage::LevelSelect => {
+            let mut items = Vec::new();
+            for level in 1..=NB_LEVEL as usize {
+                let unlocked = level <= save.data.unlocked_level;
+                let label = if !unlocked {
+                    format!("Niveau {} - verrouillé", level)
+                } else if let Some(best_score) = save.data.best_scores.get(&level) {
+                    format!(
+                        "Niveau {} - record {} ({} coups, {:.1} s)",
+                        level,
+                        best_score,
+                        save.data.best_moves.get(&level).copied().unwrap_or_default(),
+                        save.data.best_times.get(&level).copied().unwrap_or_default(),
+                    )
+                } else {
+                    format!("Niveau {}", level)
+                };
+                items.push((label, MenuButton::Level(level), unlocked));
+            }
+            items.push(("Retour".to_owned(), MenuButton::Back, true));
+            items
+        }
+        MenuPage::Pause => vec![
+            ("Reprendre".to_owned(), MenuButton::Resume, true),
+            ("Recommencer le niveau".to_owned(), MenuButton::RestartLevel, true),
+            ("Paramètres".to_owned(), MenuButton::Settings, true),
+            ("Retour au menu".to_owned(), MenuButton::QuitToMenu, true),
+        ],
+        MenuPage::End => vec![
+            ("Rejouer".to_owned(), MenuButton::PlayAgain, true),
+            ("Exporter ma partie".to_owned(), MenuButton::ExportRun, true),
+            ("Retour au menu".to_owned(), MenuButton::QuitToMenu, true),
+        ],
+        MenuPage::Settings => vec![
+            (format!("Musique : {}%", percent(settings.music_volume)), MenuButton::MusicVolume, true),
+            (format!("Effets : {}%", percent(settings.sfx_volume)), MenuButton::SfxVolume, true),
+            (format!("Plein écran : {}", if settings.fullscreen {"oui"} else {"non"}), MenuButton::Fullscreen, true),
+            (format!("Coups en attente : {}", settings.input_buffer), MenuButton::InputBuffer, true),
+            (format!("Mode : {}", settings.tick_mode.label()), MenuButton::TickMode, true),
+            (format!("Difficulté : {}", settings.difficulty.label()), MenuButton::Difficulty, true),
+            ("Commandes".to_owned(), MenuButton::Controls, true),
+            ("Retour".to_owned(), MenuButton::Back, true),
+        ],
+        MenuPage::Controls => {
+            let bindings = &controls.bindings;
+            let mut items = vec![(format!("Préréglage : {}", bindings.preset.label()), MenuButton::Preset, true)];
+            for action in Action::ALL {
+                let keys = if rebinding.action == Some(action) {
+                    "appuyez sur une touche...".to_owned()
+                } else {
+                    bindings.keys_label(action)
+                };
+                items.push((format!("{} : {}", action.label(), keys), MenuButton::Rebind(action), true));
+            }
+            items.push(("Retour".to_owned(), MenuButton::Back, true));
+            items
+        }
+    }
+}
+
+/// Texte affiché entre le titre et les boutons
+fn page_body(page: MenuPage, run_stats: &RunStats, score: &Score, save: &SaveGame) -> Vec<String> {
+    if page != MenuPage::End { return Vec::new(); }
+
+    let mut lines = vec![
+        format!("Score : {}  Pièces : {}  Record : {}", score.total, score.total_coins, save.data.best_total),
+        run_stats.total_line(),
+    ];
+    lines.extend(run_stats.level_lines());
+    if let Some(exported) = &run_stats.exported {
+        lines.push(format!("Partie exportée : {}", exported));
+    }
+    return lines;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_menu_page(
+    mut commands: Commands,
+    page: Res<MenuPage>,
+    save: Res<SaveGame>,
+    score: Res<Score>,
+    run_stats: Res<RunStats>,
+    controls: Res<Controls>,
+    rebinding: Res<Rebinding>,
+    mut selection: ResMut<MenuSelection>,
+    root_query: Query<Entity, With<MenuRoot>>,
+) {
+    for entity in root_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let items = page_items(*page, &save, &controls, &rebinding);
+    selection.index = selection.index.min(items.len() - 1);
+
+    let title = match *page {
+        MenuPage::Main => "Dungeon",
+        MenuPage::LevelSelect => "Choix du niveau",
+        MenuPage::Settings => "Paramètres",
+        MenuPage::Controls => "Commandes",
+        MenuPage::Pause => "Pause",
+        MenuPage::End => "Bravo !",
+    };
+    // l'écran de fin garde ses sprites visibles à gauche
+    let end_page = *page == MenuPage::End;
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: if end_page { AlignItems::FlexEnd } else { AlignItems::Center },
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(12.),
+                padding: UiRect::right(Val::Px(if end_page { 40. } else { 0. })),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., if end_page { 0. } else { 0.7 }).into(),
+            ..default()
+        },
+        MenuRoot,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(title, TextStyle {
+            font_size: 64.,
+            color: Color::WHITE,
+            ..default()
+        }));
+        for line in page_body(*page, &run_stats, &score, &save) {
+            parent.spawn(TextBundle::from_section(line, TextStyle {
+                font_size: 20.,
+                color: Color::WHITE,
+                ..default()
+            }));
+        }
+
+        for (index, (label, button, enabled)) in items.into_iter().enumerate() {
+            parent.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(480.),
+                        padding: UiRect::all(Val::Px(10.)),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    background_color: BUTTON_COLOR.into(),
+                    ..default()
+                },
+                button,
+                MenuItem { index, enabled },
+            )).with_children(|parent| {
+                parent.spawn(TextBundle::from_section(label, TextStyle {
+                    font_size: 28.,
+                    color: if enabled { Color::WHITE } else { Color::GRAY },
+                    ..default()
+                }));
+            });
+        }
+    });
+}
+
+/// La prochaine touche appuyée remplace celle de l'action choisie, Échap annule
+fn capture_rebind(
+    input: Res<Input<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut controls: ResMut<Controls>,
+    mut page: ResMut<MenuPage>,
+    #[cfg(feature = "dev")] console: Res<Console>,
+) {
+    // la console ouverte, ou fermée à cette image par Échap, garde le clavier
+    #[cfg(feature = "dev")]
+    if console.open || console.is_changed() { return; }
+    let Some(action) = rebinding.action else { return; };
+    if input.just_pressed(KeyCode::Escape) {
+        rebinding.action = None;
+        page.set_changed();
+        return;
+    }
+    let Some(key) = input.get_just_pressed().copied().find(|key| is_bindable(*key)) else { return; };
+    controls.bindings.rebind(action, key);
+    controls.write();
+    rebinding.action = None;
+    page.set_changed();
+}
+
+/// Clavier et manette
+#[allow(clippy::too_many_arguments)]
+fn menu_keyboard(
+    input: Res<Input<KeyCode>>,
+    gamepad: Res<GamepadActions>,
+    actions: Res<ActionState>,
+    rebinding: Res<Rebinding>,
+    page: Res<MenuPage>,
+    mut selection: ResMut<MenuSelection>,
+    item_query: Query<(&MenuItem, &MenuButton)>,
+    mut menu_event: EventWriter<MenuEvent>,
+    #[cfg(feature = "dev")] console: Res<Console>,
+) {
+    #[cfg(feature = "dev")]
+    if console.open || console.is_changed() { return; }
+    // la touche qui vient d'être choisie ne doit pas agir sur le menu
+    if rebinding.action.is_some() || rebinding.is_changed() { return; }
+    let count = item_query.iter().len();
+    if count == 0 { return; }
+
+    if input.just_pressed(KeyCode::Down) || gamepad.down_pressed {
+        selection.index = (selection.index + 1) % count;
+    }
+    if input.just_pressed(KeyCode::Up) || gamepad.up_pressed {
+        selection.index = (selection.index + count - 1) % count;
+    }
+
+    let Some((item, button)) = item_query.iter().find(|(item, _)| item.index == selection.index) else { return; };
+    if (input.any_just_pressed([KeyCode::Return, KeyCode::Space]) || gamepad.confirm) && item.enabled {
+        menu_event.send(MenuEvent { button: *button, step: 1 });
+    }
+    // un niveau verrouillé ou `Quitter` ne doivent pas s'activer avec droite
+    let adjustable = item.enabled && button.adjustable();
+    if (input.just_pressed(KeyCode::Left) || gamepad.left_pressed) && adjustable {
+        menu_event.send(MenuEvent { button: *button, step: -1 });
+    }
+    if (input.just_pressed(KeyCode::Right) || gamepad.right_pressed) && adjustable {
+        menu_event.send(MenuEvent { button: *button, step: 1 });
+    }
+    if input.just_pressed(KeyCode::Escape) || gamepad.back || actions.just_pressed(Action::Pause) {
+        match *page {
+            MenuPage::Main | MenuPage::End => (),
+            MenuPage::Pause => menu_event.send(MenuEvent { button: MenuButton::Resume, step: 1 }),
+            _ => menu_event.send(MenuEvent { button: MenuButton::Back, step: 1 }),
+        }
+    }
+}
+
+/// Souris et écran tactile passent tous les deux par `Interaction`
+fn menu_pointer(
+    mut selection: ResMut<MenuSelection>,
+    item_query: Query<(&Interaction, &MenuItem, &MenuButton), Changed<Interaction>>,
+    mut menu_event: EventWriter<MenuEvent>,
+) {
+    for (interaction, item, button) in item_query.iter() {
+        match *interaction {
+            Interaction::Hovered => selection.index = item.index,
+            Interaction::Pressed => {
+                selection.index = item.index;
+                if item.enabled {
+                    menu_event.send(MenuEvent { button: *button, step: 1 });
+                }
+            }
+            Interaction::None => (),
+        }
+    }
+}
+
+fn highlight_selection(
+    selection: Res<MenuSelection>,
+    mut item_query: Query<(&MenuItem, &mut BackgroundColor)>,
+) {
+    for (item, mut background) in item_query.iter_mut() {
+        *background = if !item.enabled {
+            DISABLED_COLOR.into()
+        } else if item.index == selection.index {
+            SELECTED_COLOR.into()
+        } else {
+            BUTTON_COLOR.into()
+        };
+    }
+}
+
+fn step_volume(volume: f32, step: i32) -> f32 {
+    let volume = volume + step as f32 * VOLUME_STEP;
+    // faire le tour quand on dépasse avec un clic
+    if volume > 1. + VOLUME_STEP / 2. { return 0.; }
+    return volume.clamp(0., 1.);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_menu_events(
+    mut menu_event: EventReader<MenuEvent>,
+    mut page: ResMut<MenuPage>,
+    mut selection: ResMut<MenuSelection>,
+    mut save: ResMut<SaveGame>,
+    mut level_res: ResMut<CurrentLevel>,
+    mut score: ResMut<Score>,
+    mut run_stats: ResMut<RunStats>,
+    mut controls: ResMut<Controls>,
+    mut rebinding: ResMut<Rebinding>,
+    mut game_state: ResMut<NextState<GameState>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut change_level_event: EventWriter<ChangeLevelEvent>,
+    mut windows: Query<&mut Window>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    for event in menu_event.read() {
+        let mut start_level = None;
+        match event.button {
+            // gauche / droite ne servent qu'aux réglages
+            MenuButton::MusicVolume => {
+                save.data.settings.music_volume = step_volume(save.data.settings.music_volume, event.step);
+            }
+            MenuButton::SfxVolume => {
+                save.data.settings.sfx_volume = step_volume(save.data.settings.sfx_volume, event.step);
+            }
+            MenuButton::Fullscreen => {
+                save.data.settings.fullscreen = !save.data.settings.fullscreen;
+                for mut window in windows.iter_mut() {
+                    window.mode = save.data.settings.window_mode();
+                }
+            }
+            MenuButton::InputBuffer => {
+                let sizes = MAX_INPUT_BUFFER as i32 + 1;
+                save.data.settings.input_buffer = (save.data.settings.input_buffer as i32 + event.step).rem_euclid(sizes) as usize;
+            }
+            MenuButton::TickMode => {
+                save.data.settings.tick_mode = save.data.settings.tick_mode.next();
+            }
+            MenuButton::Difficulty => {
+                save.data.settings.difficulty = save.data.settings.difficulty.next(event.step);
+            }
+            MenuButton::Preset => {
+                let preset = controls.bindings.preset.next(event.step);
+                controls.bindings = Bindings::from_preset(preset);
+                controls.write();
+                page.set_changed();
+            }
+            _ if event.step < 0 => continue,
+            MenuButton::Controls => {
+                *page = MenuPage::Controls;
+                selection.index = 0;
+            }
+            MenuButton::Rebind(action) => {
+                rebinding.action = Some(action);
+                page.set_changed();
+            }
+            MenuButton::NewGame => start_level = Some(1),
+            MenuButton::Continue => start_level = Some(save.data.unlocked_level.clamp(1, NB_LEVEL as usize)),
+            MenuButton::Level(level) => start_level = Some(level),
+            MenuButton::PlayAgain => start_level = Some(1),
+            MenuButton::ExportRun => {
+                let report = run_report(&run_stats, &score, &save);
+                match export_run(&report) {
+                    Ok(path) => run_stats.exported = Some(path),
+                    Err(error) => warn!("could not export run: {}", error),
+                }
+                page.set_changed();
+            }
+            MenuButton::LevelSelect => {
+                *page = MenuPage::LevelSelect;
+                selection.index = 0;
+            }
+            MenuButton::Settings => {
+                *page = MenuPage::Settings;
+                selection.index = 0;
+            }
+            MenuButton::Back => {
+                *page = if *page == MenuPage::Controls {
+                    MenuPage::Settings
+                } else if *pause_state.get() == PauseState::Paused {
+                    MenuPage::Pause
+                } else {
+                    MenuPage::Main
+                };
+                selection.index = 0;
+            }
+            MenuButton::Resume => {
+                next_pause_state.set(PauseState::Running);
+            }
+            MenuButton::RestartLevel => {
+                change_level_event.send(ChangeLevelEvent { new_level: false });
+                next_pause_state.set(PauseState::Running);
+            }
+            MenuButton::QuitToMenu => {
+                next_pause_state.set(PauseState::Running);
+                game_state.set(GameState::Menu);
+            }
+            MenuButton::Quit => {
+                app_exit.send(AppExit);
+            }
+        }
+
+        if matches!(event.button, MenuButton::MusicVolume | MenuButton::SfxVolume | MenuButton::Fullscreen | MenuButton::InputBuffer | MenuButton::TickMode | MenuButton::Difficulty) {
+            save.write();
+            // reconstruire la page pour afficher la nouvelle valeur
+            page.set_changed();
+        }
+
+        if let Some(level) = start_level {
+            level_res.level = level;
+            *score = Score::default();
+            *run_stats = RunStats::default();
+            game_state.set(GameState::Game);
+            return;
+        }
+    }
+}
@@ -2,8 +2,6 @@ use bevy::{ecs::component::Component, math::{Vec3, Vec2}};
 
 use crate::{Direction, TOP, RIGHT};
 
-const ANIMATION_SPEED: f32 = 1.5;
-
 #[derive(Component)]
 pub struct Monster {
     game_x: i32,
@@ -11,6 +9,8 @@ pub struct Monster {
     is_animating: bool,
     direction: Direction,
     pub has_moved: bool,
+    /// Ordre d'apparition, les monstres jouent dans cet ordre
+    order: u32,
 }
 
 impl Monster {
@@ -29,12 +29,13 @@ impl Monster {
         self.game_y = game_y;
     }
 
-    pub fn animate(&mut self, current_position: &Vec3) -> Vec3 {
+    /// `step` : distance parcourue pendant cette image
+    pub fn animate(&mut self, current_position: &Vec3, step: f32) -> Vec3 {
 
         let target = Monster::get_translation(self.game_x, self.game_y);
 
         // Voir si à la fin du trajet
-        if target.distance(current_position.truncate()) < ANIMATION_SPEED*2. {
+        if target.distance(current_position.truncate()) <= step*2. {
             self.is_animating = false;
             self.direction = Direction::No;
             return target.extend(0.5);
@@ -44,8 +45,8 @@ impl Monster {
         // Calculer étape intermédiaire
         let angle = ((current_position.x-target.x)/target.distance(current_position.truncate())).asin();
         let temporary_position =  Vec2::new(
-            -angle.sin()*ANIMATION_SPEED + current_position.x,
-            -angle.cos()*ANIMATION_SPEED + current_position.y
+            -angle.sin()*step + current_position.x,
+            -angle.cos()*step + current_position.y
         );
 
         // Sens de l'animation
@@ -58,22 +59,39 @@ impl Monster {
         return  temporary_position.extend(0.5);
     }
 
-    pub(crate) fn new(game_x: i32, game_y: i32) -> Monster{
-        return Monster { game_x: game_x, game_y:game_y, is_animating: false, direction: Direction::No, has_moved: false };
+    #[allow(clippy::redundant_field_names)]
+    pub(crate) fn new(game_x: i32, game_y: i32, order: u32) -> Monster{
+        return Monster { game_x: game_x, game_y:game_y, is_animating: false, direction: Direction::No, has_moved: false, order };
     }
 
     pub(crate) fn get_translation(game_x: i32, game_y: i32) -> Vec2 {
         return Vec2::new(25. +(game_x*50-RIGHT) as f32, 25.+(game_y*50-TOP) as f32);
     }
 
-    pub(crate) fn game_x(&self) -> i32 {
+    pub fn game_x(&self) -> i32 {
         return self.game_x;
     }
 
-    pub(crate) fn game_y(&self) -> i32 {
+    pub fn game_y(&self) -> i32 {
         return self.game_y;
     }
 
+    /// Sans animation, pour annuler un coup
+    pub(crate) fn set_position(&mut self, game_x: i32, game_y: i32) {
+        self.game_x = game_x;
+        self.game_y = game_y;
+        self.is_animating = false;
+        self.direction = Direction::No;
+    }
+
+    pub fn is_animating(&self) -> bool {
+        return self.is_animating;
+    }
+
+    pub fn order(&self) -> u32 {
+        return self.order;
+    }
+
     pub(crate) fn direction(&self) -> Direction {
         return self.direction;
     }
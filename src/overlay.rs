@@ -0,0 +1,168 @@
+use bevy::{app::{App, Plugin, Update}, ecs::{entity::Entity, query::{With, Without}, schedule::{common_conditions::in_state, IntoSystemConfigs, OnExit}, system::{Commands, Query, Res, ResMut, Resource}}, gizmos::gizmos::Gizmos, input::{keyboard::KeyCode, Input}, math::{vec2, Vec2}, render::color::Color, sprite::Anchor, text::{Text, Text2dBundle, TextStyle}, transform::components::Transform};
+
+use crate::*;
+
+const OVERLAY_KEY: KeyCode = KeyCode::F2;
+const CELL: f32 = 50.;
+const GRID_COLOR: Color = Color::rgba(1., 1., 1., 0.15);
+const TARGET_COLOR: Color = Color::rgb(1., 0.85, 0.2);
+const BLOCKED_COLOR: Color = Color::rgb(1., 0.2, 0.2);
+
+pub struct DebugOverlayPlugin;
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<DebugOverlay>()
+            .add_systems(OnExit(GameState::Game), despawn_labels)
+            .add_systems(Update, (
+                toggle_overlay,
+                sync_cell_labels,
+                draw_grid,
+                inspect_entities,
+            ).chain().run_if(in_state(GameState::Game)));
+    }
+}
+
+/// Grille, coordonnées des cases et état des entités, affichés avec F2
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+/// Coordonnées d'une case
+#[derive(Component)]
+struct CellLabel;
+
+/// État d'une entité du jeu, suit l'entité
+#[derive(Component)]
+struct InspectorLabel(Entity);
+
+/// Coin en bas à gauche d'une case, dans le monde
+fn cell_origin(game_x: i32, game_y: i32) -> Vec2 {
+    return vec2((game_x*50-RIGHT) as f32, (game_y*50-TOP) as f32);
+}
+
+fn label_style(font_size: f32, color: Color) -> TextStyle {
+    return TextStyle { font_size, color, ..default() };
+}
+
+fn toggle_overlay(keyboard: Res<Input<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keyboard.just_pressed(OVERLAY_KEY) {
+        overlay.enabled = !overlay.enabled;
+    }
+}
+
+fn sync_cell_labels(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    labels: Query<Entity, With<CellLabel>>,
+) {
+    if !overlay.is_changed() { return; }
+    for entity in labels.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !overlay.enabled { return; }
+
+    for game_x in 0..SCREEN_GAME_X {
+        for game_y in 0..SCREEN_GAME_Y {
+            commands.spawn((
+                Text2dBundle {
+                    text: Text::from_section(format!("{},{}", game_x, game_y), label_style(10., GRID_COLOR.with_a(0.6))),
+                    text_anchor: Anchor::BottomLeft,
+                    transform: Transform::from_translation((cell_origin(game_x, game_y) + vec2(2., 2.)).extend(5.)),
+                    ..default()
+                },
+                CellLabel,
+            ));
+        }
+    }
+}
+
+fn draw_grid(
+    overlay: Res<DebugOverlay>,
+    mut gizmos: Gizmos,
+    player: Query<&Player>,
+    monster_query: Query<&Monster>,
+    wall_query: Query<&Wall>,
+) {
+    if !overlay.enabled { return; }
+
+    let bottom_left = cell_origin(0, 0);
+    let top_right = cell_origin(SCREEN_GAME_X, SCREEN_GAME_Y);
+    for game_x in 0..=SCREEN_GAME_X {
+        let x = cell_origin(game_x, 0).x;
+        gizmos.line_2d(vec2(x, bottom_left.y), vec2(x, top_right.y), GRID_COLOR);
+    }
+    for game_y in 0..=SCREEN_GAME_Y {
+        let y = cell_origin(0, game_y).y;
+        gizmos.line_2d(vec2(bottom_left.x, y), vec2(top_right.x, y), GRID_COLOR);
+    }
+
+    // case visée par chaque monstre, comme dans `move_monster`
+    let Ok(player) = player.get_single() else { return; };
+    let Some(player_game_x) = player.game_x else { return; };
+    for monster in monster_query.iter() {
+        let target_x = if monster.game_x() > player_game_x {
+            monster.game_x()-1
+        } else if monster.game_x() < player_game_x {
+            monster.game_x()+1
+        } else {
+            continue;
+        };
+        let blocked = wall_query.iter().any(|wall| wall.game_x == target_x && wall.game_y == monster.game_y())
+            || monster_query.iter().any(|other| other.game_x() == target_x && other.game_y() == monster.game_y());
+        let center = cell_origin(target_x, monster.game_y()) + Vec2::splat(CELL / 2.);
+        gizmos.rect_2d(center, 0., Vec2::splat(CELL - 4.), if blocked { BLOCKED_COLOR } else { TARGET_COLOR });
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn inspect_entities(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    player: Query<(Entity, &Player, &Transform)>,
+    monster_query: Query<(Entity, &Monster, &Transform)>,
+    wall_query: Query<&Wall>,
+    mut labels: Query<(Entity, &InspectorLabel, &mut Text, &mut Transform), (Without<Player>, Without<Monster>)>,
+) {
+    let mut wanted: Vec<(Entity, String, Vec2)> = Vec::new();
+    if overlay.enabled {
+        for (entity, player, transform) in player.iter() {
+            let on_the_ground = player.on_the_ground(wall_query.iter(), monster_query.iter().map(|(_, monster, _)| monster));
+            let text = format!("on_the_ground: {}\nis_animating: {}", on_the_ground, player.is_animating);
+            wanted.push((entity, text, transform.translation.truncate() + vec2(CELL / 2., CELL * 1.5)));
+        }
+        for (entity, monster, transform) in monster_query.iter() {
+            let text = format!("#{} has_moved: {}\nis_animating: {}", monster.order(), monster.has_moved, monster.is_animating());
+            wanted.push((entity, text, transform.translation.truncate() + vec2(0., CELL)));
+        }
+    }
+
+    // mettre à jour les étiquettes existantes, enlever celles des entités disparues
+    for (label_entity, label, mut text, mut transform) in labels.iter_mut() {
+        match wanted.iter().position(|(entity, _, _)| *entity == label.0) {
+            Some(index) => {
+                let (_, value, position) = wanted.swap_remove(index);
+                text.sections[0].value = value;
+                transform.translation = position.extend(6.);
+            }
+            None => commands.entity(label_entity).despawn(),
+        }
+    }
+    for (entity, value, position) in wanted {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(value, label_style(12., Color::WHITE)),
+                transform: Transform::from_translation(position.extend(6.)),
+                ..default()
+            },
+            InspectorLabel(entity),
+        ));
+    }
+}
+
+fn despawn_labels(mut commands: Commands, labels: Query<Entity, With<CellLabel>>, inspector: Query<Entity, With<InspectorLabel>>) {
+    for entity in labels.iter().chain(inspector.iter()) {
+        commands.entity(entity).despawn();
+    }
+}
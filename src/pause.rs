@@ -0,0 +1,66 @@
+use bevy::{app::{App, Plugin, Update}, ecs::{query::{Changed, With}, schedule::{common_conditions::in_state, IntoSystemConfigs, NextState, OnEnter, OnExit}, system::{Commands, Query, Res, ResMut}}, hierarchy::BuildChildren, render::color::Color, text::TextStyle, time::{Time, Virtual}, ui::{node_bundles::{ButtonBundle, TextBundle}, Interaction, JustifyContent, PositionType, Style, UiRect, Val}};
+
+use crate::*;
+
+pub struct PausePlugin;
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::Game), spawn_pause_button)
+            .add_systems(Update, enter_pause.run_if(in_state(GameState::Game)).run_if(in_state(PauseState::Running)))
+            .add_systems(OnEnter(PauseState::Paused), (
+                pause_time,
+                open_pause_page,
+            ))
+            .add_systems(OnExit(PauseState::Paused), resume_time);
+    }
+}
+
+#[derive(Component)]
+pub struct PauseButton;
+
+fn spawn_pause_button(mut commands: Commands) {
+    commands.spawn((
+        ButtonBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                right: Val::Px(10.),
+                width: Val::Px(48.),
+                height: Val::Px(48.),
+                padding: UiRect::all(Val::Px(6.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: Color::rgba(0.2, 0.2, 0.25, 0.8).into(),
+            ..default()
+        },
+        PauseButton,
+        InGame,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("II", TextStyle {
+            font_size: 32.,
+            color: Color::WHITE,
+            ..default()
+        }));
+    });
+}
+
+fn enter_pause(
+    actions: Res<ActionState>,
+    button_query: Query<&Interaction, (Changed<Interaction>, With<PauseButton>)>,
+    mut pause_state: ResMut<NextState<PauseState>>,
+) {
+    let button_pressed = button_query.iter().any(|interaction| *interaction == Interaction::Pressed);
+    if actions.just_pressed(Action::Pause) || button_pressed {
+        pause_state.set(PauseState::Paused);
+    }
+}
+
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
@@ -1,4 +1,4 @@
-use bevy::{app::{App, Plugin}, asset::AssetServer, ecs::system::{Commands, Res}, math::{vec2, vec3, Vec2, Vec3}, sprite::{Anchor, Sprite, SpriteBundle}, transform::components::Transform};
+use bevy::{app::{App, Plugin}, ecs::system::{Commands, Res}, math::{vec2, vec3, Vec2, Vec3}, sprite::{Anchor, SpriteSheetBundle, TextureAtlasSprite}, transform::components::Transform};
 
 use crate::*;
 use crate::math::get_distance;
@@ -7,12 +7,17 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app
+            .add_event::<PlayerActionEvent>()
             .add_systems(OnEnter(GameState::Game), spawn_player)
-            .add_systems(Update, move_player.run_if(in_state(GameState::Game)));
+            .add_systems(Update, move_player.in_set(TurnSet::Input).run_if(in_state(GameState::Game)).run_if(in_state(PauseState::Running)).run_if(transition_idle));
     }
 }
 
-const FALLING_SPEED: f32 = 2.;
+/// Coup compté dans le score, après le déplacement logique du joueur
+#[derive(Event, Clone, Copy)]
+pub struct PlayerActionEvent {
+    pub action: Action,
+}
 
 #[derive(Component)]
 pub struct Player {
@@ -66,7 +71,15 @@ impl Player {
         self.check_if_outdoor();
     }
 
-    pub fn animate(&mut self, current_position: &Vec3) -> (Vec3, bool) {
+    /// Un mur ou une chauve-souris juste en dessous
+    pub fn on_the_ground<'a>(&self, walls: impl IntoIterator<Item = &'a Wall>, monsters: impl IntoIterator<Item = &'a Monster>) -> bool {
+        let (Some(game_x), Some(game_y)) = (self.game_x, self.game_y) else { return false; };
+        return walls.into_iter().any(|wall| wall.game_x == game_x && wall.game_y == game_y-1)
+            || monsters.into_iter().any(|monster| monster.game_x() == game_x && monster.game_y() == game_y-1);
+    }
+
+    /// `step` et `falling_step` : distance parcourue pendant cette image
+    pub fn animate(&mut self, current_position: &Vec3, step: f32, falling_step: f32) -> (Vec3, bool) {
         if self.game_x.is_none() || self.game_y.is_none() { return (vec3(0., 0., 0.), false); }
 
         let target = Vec2::new(9. + (self.game_x.unwrap()*50-RIGHT) as f32, (self.game_y.unwrap()*50-TOP) as f32);
@@ -74,21 +87,21 @@ impl Player {
         if current_position.x != target.x { // On le bouge sur l'axe des X
             self.is_animating = true;
             // Si la co X est proche de destination
-            if get_distance(target.x, current_position.x) < ANIMATION_SPEED {
+            if get_distance(target.x, current_position.x) <= step {
                 return (vec3(target.x, current_position.y, 1.), false);
             }
 
             // sinon on bouge progressivement
             if current_position.x > target.x { // voir de quel coté aller
                 self.direction = Direction::Left;
-                return (vec3(current_position.x-ANIMATION_SPEED, current_position.y, 1.), false);
+                return (vec3(current_position.x-step, current_position.y, 1.), false);
             } else {
                 self.direction = Direction::Right;
-                return (vec3(current_position.x+ANIMATION_SPEED, current_position.y, 1.), false);
+                return (vec3(current_position.x+step, current_position.y, 1.), false);
             }
         } else {
             // si Y proche destination
-            if get_distance(current_position.y, target.y) < ANIMATION_SPEED {
+            if get_distance(current_position.y, target.y) <= step.max(falling_step) {
                 self.direction = Direction::No;
                 self.is_animating = false;
                 return (target.extend(1.), true);
@@ -98,20 +111,20 @@ impl Player {
             self.is_animating = true;
             if current_position.y > target.y { // voir de quel coté aller
                 self.direction = Direction::Bottom;
-                return (vec3(target.x, current_position.y-FALLING_SPEED, 1.), false);
+                return (vec3(target.x, current_position.y-falling_step, 1.), false);
             } else {
                 self.direction = Direction::No;
-                return (vec3(target.x, current_position.y+ANIMATION_SPEED, 1.), false);
+                return (vec3(target.x, current_position.y+step, 1.), false);
             }
         }
     }
 }
 
-pub fn spawn_player(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn spawn_player(mut commands: Commands, textures_ressource: Res<TexturesRessource>) {
     commands.spawn((
-        SpriteBundle {
-            texture: asset_server.load("textures/entity/hero1.png"),
-            sprite: Sprite {
+        SpriteSheetBundle {
+            texture_atlas: textures_ressource.player.clone(),
+            sprite: TextureAtlasSprite {
                 anchor: Anchor::BottomLeft,
                 ..Default::default()
             },
@@ -122,11 +135,14 @@ pub fn spawn_player(mut commands: Commands, asset_server: Res<AssetServer>) {
             },
             ..Default::default()
         },
+        player_animation(),
         Player { game_x: None, game_y: None, is_animating: false, direction: Direction::No, has_change_pos:false },
+        InGame,
     ));
 }
 
-fn move_player(
+#[allow(clippy::too_many_arguments)]
+pub fn move_player(
     mut player: Query<&mut Player>,
     mut player_transform: Query<&mut Transform, With<Player>>,
 
@@ -135,76 +151,40 @@ fn move_player(
     red_door_query: Query<&RedDoor>,
     wall_query: Query<&Wall>,
     mut chest_query: Query<&mut Chest>,
-    mut button_query: Query<Entity, With<StartButton>>,
-
-    buttons: Res<Input<MouseButton>>,
-    mut begin_click: ResMut<BeginClick>,
-    q_windows: Query<&Window, With<PrimaryWindow>>,
 
-    mut change_level_event: EventWriter<ChangeLevelEvent>,
+    mut level_end_event: EventWriter<LevelEndEvent>,
     mut tick_event: EventWriter<TickEvent>,
-
-    input: Res<Input<KeyCode>>,
-    mut commands: Commands,
+    mut sound_event: EventWriter<SoundEvent>,
+    mut action_event: EventWriter<PlayerActionEvent>,
+
+    actions: Res<ActionState>,
+    mut queue: ResMut<InputQueue>,
+    clock: Res<TickClock>,
+    #[cfg(feature = "dev")] cheats: Res<DevCheats>,
+    mut score: ResMut<Score>,
 ) {
     let mut player = player.single_mut();
     if player.game_x.is_none() || player.game_y.is_none() { return; }
 
-    // Obtenir les mouvements de souris
-    let (mouse_left, mouse_right, mouse_tap) = {
-        if q_windows.single().cursor_position().is_none() { return; }
-        let current_position = q_windows.single().cursor_position().unwrap();
-
-        if buttons.just_pressed(MouseButton::Left) {
-            begin_click.position = Some(current_position);
-        }
-
-        let mut mouse_tap = false;
-        let mut mouse_left = false;
-        let mut mouse_right = false;
-        if begin_click.position.is_some() {
-            let begin_click_position = begin_click.position.unwrap().clone();
-            if buttons.just_released(MouseButton::Left) {
-                // Voir si il y a un mouvement
-                if begin_click_position.distance(current_position) > 100. { // scroll
-                    if begin_click_position.x < current_position.x {
-                        mouse_right = true;
-                    } else {
-                        mouse_left = true;
-                    }
-                } else { // click
-                    mouse_tap = true;
-                }
-            }
-        }
-
-        (mouse_left, mouse_right, mouse_tap)
-    };
-
-    // si click faire disparaitre le bouton click to start
-    if mouse_tap && !button_query.is_empty() {
-        for button in &mut button_query {
-            commands.entity(button).despawn();
-        }
-    }
-
-
     // si le joueur est en train de tomber l'empecher de bouger
-    let mut on_the_ground = false;
-    for wall in wall_query.iter() {
-        if wall.game_x == player.game_x.unwrap() && wall.game_y == player.game_y.unwrap()-1 {
-            on_the_ground = true;
-        }
-    }
-    for monster in monster_query.iter() {
-        if player.game_x.unwrap() == monster.game_x() && player.game_y.unwrap()-1 == monster.game_y() {
-            on_the_ground = true;
+    let on_the_ground = player.on_the_ground(wall_query.iter(), monster_query.iter());
+    #[cfg(feature = "dev")]
+    let on_the_ground = on_the_ground || cheats.noclip;
+
+    // pendant un déplacement ou une chute, garder le coup pour la fin du tour
+    if !on_the_ground || player.is_animating {
+        for action in [Action::MoveLeft, Action::MoveRight, Action::Use] {
+            if actions.just_pressed(action) {
+                queue.push(action);
+            }
         }
+        return;
     }
-    if !on_the_ground { return; }
+    let action = queue.pop().or_else(|| [Action::MoveLeft, Action::MoveRight, Action::Use].into_iter().find(|action| actions.pressed(*action)));
+    let _span = action.map(|action| debug_span!("player_action", ?action, x = player.game_x.unwrap(), y = player.game_y.unwrap()).entered());
 
     // gerer les mouvements
-    if (input.pressed(KeyCode::Left) || mouse_left) && !player.is_animating {
+    if action == Some(Action::MoveLeft) {
         // vérifier qu'il n'y a pas de murs
         let mut can_go = true;
         for wall in wall_query.iter() {
@@ -213,12 +193,23 @@ fn move_player(
                 break;
             }
         }
+        #[cfg(feature = "dev")]
+        if cheats.noclip {
+            can_go = true;
+        }
         if can_go {
             player.move_with_direction(Direction::Left);
-            tick_event.send(TickEvent);
+            score.moves += 1;
+            action_event.send(PlayerActionEvent { action: Action::MoveLeft });
+            sound_event.send(SoundEvent { sfx: Sfx::Footstep });
+            if clock.is_turn_based() {
+                tick_event.send(TickEvent);
+            }
+        } else {
+            trace!("wall in the way");
         }
     }
-    else if (input.pressed(KeyCode::Right) || mouse_right) && !player.is_animating {
+    else if action == Some(Action::MoveRight) {
         // vérifier qu'il n'y a pas de murs
         let mut can_go = true;
         for wall in wall_query.iter() {
@@ -227,21 +218,38 @@ fn move_player(
                 break;
             }
         }
+        #[cfg(feature = "dev")]
+        if cheats.noclip {
+            can_go = true;
+        }
         if can_go {
             player.move_with_direction(Direction::Right);
-            tick_event.send(TickEvent);
+            score.moves += 1;
+            action_event.send(PlayerActionEvent { action: Action::MoveRight });
+            sound_event.send(SoundEvent { sfx: Sfx::Footstep });
+            if clock.is_turn_based() {
+                tick_event.send(TickEvent);
+            }
+        } else {
+            trace!("wall in the way");
         }
     }
-    else if (input.pressed(KeyCode::Up) || mouse_tap) && !player.is_animating {
+    else if action == Some(Action::Use) {
         // Blue Door
         for door in blue_door_query.iter() {
             if door.game_x == player.game_x.unwrap() && door.game_y == player.game_y.unwrap() {
                 // teleport player to other blue door 
                 for tp_door in blue_door_query.iter() {
-                    if door.game_x != tp_door.game_x && door.game_y != tp_door.game_y && chrono::Local::now().timestamp_millis() > 500 {
+                    if door.game_x != tp_door.game_x && door.game_y != tp_door.game_y {
                         let mut player_transform = player_transform.single_mut();
                         player_transform.translation = player.move_without_animation(tp_door.game_x, tp_door.game_y).extend(0.);
-                        tick_event.send(TickEvent);
+                        score.moves += 1;
+                        action_event.send(PlayerActionEvent { action: Action::Use });
+                        debug!("teleported to {} {}", tp_door.game_x, tp_door.game_y);
+                        sound_event.send(SoundEvent { sfx: Sfx::Teleport });
+                        if clock.is_turn_based() {
+                            tick_event.send(TickEvent);
+                        }
                         return;
                     }
                 }
@@ -257,7 +265,10 @@ fn move_player(
             }
         }
         if red_door.game_x == player.game_x.unwrap() && red_door.game_y == player.game_y.unwrap() && all_chest_open {
-            change_level_event.send(ChangeLevelEvent {new_level:true});
+            score.moves += 1;
+            action_event.send(PlayerActionEvent { action: Action::Use });
+            debug!("red door opened");
+            level_end_event.send(LevelEndEvent { kind: LevelEnd::Completed });
             return;
         }
 
@@ -265,7 +276,13 @@ fn move_player(
         for mut chest in chest_query.iter_mut() {
             if chest.game_x == player.game_x.unwrap() && chest.game_y == player.game_y.unwrap() {
                 chest.open();
-                tick_event.send(TickEvent);
+                score.moves += 1;
+                action_event.send(PlayerActionEvent { action: Action::Use });
+                debug!("chest opened at {} {}", chest.game_x, chest.game_y);
+                sound_event.send(SoundEvent { sfx: Sfx::ChestOpen });
+                if clock.is_turn_based() {
+                    tick_event.send(TickEvent);
+                }
                 return;
             }
         }
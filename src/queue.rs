@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+
+use bevy::{app::{App, Plugin, Update}, ecs::{event::EventReader, schedule::{common_conditions::in_state, IntoSystemConfigs, OnEnter}, system::{Res, ResMut, Resource}}};
+
+use crate::*;
+
+/// Nombre maximal de coups gardés en attente
+pub const MAX_INPUT_BUFFER: usize = 2;
+
+pub struct InputQueuePlugin;
+impl Plugin for InputQueuePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<InputQueue>()
+            .add_systems(OnEnter(PauseState::Paused), clear_queue)
+            .add_systems(Update, (
+                resize_queue,
+                clear_queue_on_level_change.after(change_level_event_listener),
+            ).run_if(in_state(GameState::Game)));
+    }
+}
+
+/// Coups joués pendant que le joueur bouge ou tombe, rejoués dès que le tour est fini
+#[derive(Resource)]
+pub struct InputQueue {
+    actions: VecDeque<Action>,
+    capacity: usize,
+}
+
+impl Default for InputQueue {
+    fn default() -> Self {
+        InputQueue { actions: VecDeque::new(), capacity: MAX_INPUT_BUFFER }
+    }
+}
+
+impl InputQueue {
+    /// Ignoré si la file est pleine
+    pub fn push(&mut self, action: Action) {
+        if self.actions.len() < self.capacity {
+            self.actions.push_back(action);
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Action> {
+        return self.actions.pop_front();
+    }
+
+    pub fn clear(&mut self) {
+        self.actions.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.actions.is_empty();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.actions.len();
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.min(MAX_INPUT_BUFFER);
+        self.actions.truncate(self.capacity);
+    }
+}
+
+fn resize_queue(save: Res<SaveGame>, mut queue: ResMut<InputQueue>) {
+    if queue.capacity != save.data.settings.input_buffer {
+        queue.set_capacity(save.data.settings.input_buffer);
+    }
+}
+
+fn clear_queue(mut queue: ResMut<InputQueue>) {
+    queue.clear();
+}
+
+fn clear_queue_on_level_change(
+    mut change_level_event: EventReader<ChangeLevelEvent>,
+    mut queue: ResMut<InputQueue>,
+) {
+    if change_level_event.read().last().is_none() { return; }
+    queue.clear();
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use bevy::{app::{App, Plugin, Update}, ecs::{event::{EventReader, EventWriter}, schedule::{common_conditions::in_state, IntoSystemConfigs}, system::{Res, ResMut, Resource}}, time::{Time, Timer, TimerMode}};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+pub struct RealtimePlugin;
+impl Plugin for RealtimePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TickClock>()
+            // l'horloge est remise à zéro avant le premier tour du nouveau niveau
+            .add_systems(Update, configure_tick_clock.after(change_level_event_listener).before(TurnSet::Input).run_if(in_state(GameState::Game)))
+            .add_systems(Update, realtime_tick.after(move_player).in_set(TurnSet::Input).run_if(in_state(GameState::Game)).run_if(in_state(PauseState::Running)).run_if(transition_idle));
+    }
+}
+
+/// Quand les monstres jouent
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum TickMode {
+    /// Après chaque action du joueur
+    #[default]
+    Turns,
+    /// À intervalle fixe, même si le joueur ne fait rien
+    RealTime,
+}
+
+impl TickMode {
+    pub fn label(&self) -> &'static str {
+        return match self {
+            TickMode::Turns => "tour par tour",
+            TickMode::RealTime => "temps réel",
+        };
+    }
+
+    pub fn next(&self) -> TickMode {
+        return match self {
+            TickMode::Turns => TickMode::RealTime,
+            TickMode::RealTime => TickMode::Turns,
+        };
+    }
+
+    /// Valeur de la ligne `mode=` d'un niveau
+    pub fn parse(value: &str) -> Option<TickMode> {
+        return match value {
+            "turns" => Some(TickMode::Turns),
+            "realtime" => Some(TickMode::RealTime),
+            _ => None,
+        };
+    }
+}
+
+/// Vitesse des monstres en temps réel
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn label(&self) -> &'static str {
+        return match self {
+            Difficulty::Easy => "facile",
+            Difficulty::Normal => "normale",
+            Difficulty::Hard => "difficile",
+        };
+    }
+
+    pub fn next(&self, step: i32) -> Difficulty {
+        let difficulties = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+        let index = difficulties.iter().position(|difficulty| difficulty == self).unwrap_or(0) as i32;
+        return difficulties[(index + step).rem_euclid(difficulties.len() as i32) as usize];
+    }
+
+    /// Secondes entre deux tours des monstres
+    pub fn tick_interval(&self) -> f32 {
+        return match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 0.6,
+        };
+    }
+}
+
+/// Mode du niveau en cours, le niveau peut imposer le sien avec `mode=`
+#[derive(Resource)]
+pub struct TickClock {
+    pub mode: TickMode,
+    timer: Timer,
+}
+
+impl Default for TickClock {
+    fn default() -> Self {
+        TickClock { mode: TickMode::Turns, timer: Timer::from_seconds(Difficulty::Normal.tick_interval(), TimerMode::Repeating) }
+    }
+}
+
+impl TickClock {
+    /// Les actions du joueur font jouer les monstres
+    pub fn is_turn_based(&self) -> bool {
+        return self.mode == TickMode::Turns;
+    }
+}
+
+fn configure_tick_clock(
+    mut change_level_event: EventReader<ChangeLevelEvent>,
+    save: Res<SaveGame>,
+    level_res: Res<CurrentLevel>,
+    level_maps: Res<LevelMaps>,
+    level_assets: Res<Assets<LevelAsset>>,
+    mut clock: ResMut<TickClock>,
+) {
+    let settings = &save.data.settings;
+    let mode = level_maps.current(&level_res, &level_assets)
+        .and_then(|level| level.mode)
+        .unwrap_or(settings.tick_mode);
+    let interval = Duration::from_secs_f32(settings.difficulty.tick_interval());
+
+    // un nouveau niveau laisse un intervalle complet avant le premier tour
+    let level_changed = change_level_event.read().last().is_some();
+    if level_changed || clock.mode != mode || clock.timer.duration() != interval {
+        clock.mode = mode;
+        clock.timer.set_duration(interval);
+        clock.timer.reset();
+    }
+}
+
+/// Le temps virtuel s'arrête pendant la pause, l'horloge aussi
+fn realtime_tick(
+    time: Res<Time>,
+    mut clock: ResMut<TickClock>,
+    mut tick_event: EventWriter<TickEvent>,
+) {
+    if clock.is_turn_based() { return; }
+    if clock.timer.tick(time.delta()).just_finished() {
+        tick_event.send(TickEvent);
+    }
+}
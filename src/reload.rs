@@ -0,0 +1,132 @@
+use bevy::{app::{App, Plugin, Update}, asset::AssetEvent, ecs::{entity::Entity, event::{EventReader, EventWriter}, query::With, schedule::{common_conditions::in_state, IntoSystemConfigs, OnExit}, system::{Commands, Query, Res, ResMut, Resource}}, hierarchy::{BuildChildren, DespawnRecursiveExt}, render::color::Color, text::TextStyle, ui::{node_bundles::{NodeBundle, TextBundle}, PositionType, Style, UiRect, Val}};
+
+use crate::*;
+
+pub struct HotReloadPlugin;
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<HotReload>()
+            .add_systems(OnExit(GameState::Game), clear_reload_errors)
+            .add_systems(Update, (
+                reload_modified_level.before(change_level_event_listener),
+                keep_player_position.after(change_level_event_listener),
+                show_reload_errors,
+            ).run_if(in_state(GameState::Game)));
+    }
+}
+
+/// Carte du niveau courant modifiée sur le disque, avec la feature `dev` ou la commande `reload`
+#[derive(Resource, Default)]
+pub struct HotReload {
+    /// Case du joueur à retrouver dans le niveau reconstruit
+    keep_player: Option<(i32, i32)>,
+    /// Erreurs de la dernière carte relue, le niveau affiché reste l'ancien
+    pub errors: Vec<String>,
+}
+
+#[derive(Component)]
+struct ReloadErrorText;
+
+pub fn reload_modified_level(
+    mut asset_events: EventReader<AssetEvent<LevelAsset>>,
+    level_res: Res<CurrentLevel>,
+    mut level_maps: ResMut<LevelMaps>,
+    level_assets: Res<Assets<LevelAsset>>,
+    mut hot_reload: ResMut<HotReload>,
+    player: Query<&Player>,
+    mut change_level_event: EventWriter<ChangeLevelEvent>,
+) {
+    for event in asset_events.read() {
+        let AssetEvent::Modified { id } = event else { continue; };
+        let Some(index) = level_maps.maps_handle.iter().position(|handle| handle.id() == *id) else { continue; };
+        let Some(level) = level_assets.get(*id) else { continue; };
+        let is_current = index + 1 == level_res.level;
+
+        let errors = match LevelGrid::parse(&level.map) {
+            Ok(grid) => {
+                let errors = grid.validate();
+                if errors.is_empty() {
+                    if let Some(copy) = level_maps.maps.get_mut(index) {
+                        *copy = level.clone();
+                    }
+                    if !is_current {
+                        info!("level {} reloaded", index + 1);
+                        continue;
+                    }
+                    hot_reload.errors.clear();
+                    // le joueur reste sur sa case si elle n'est pas devenue un mur, sinon le niveau recommence
+                    hot_reload.keep_player = player.get_single().ok()
+                        .and_then(|player| Some((player.game_x?, player.game_y?)))
+                        .filter(|position| !grid.walls().contains(position));
+                    info!("level {} reloaded, player {}", index + 1, if hot_reload.keep_player.is_some() {"kept in place"} else {"restarted"});
+                    change_level_event.send(ChangeLevelEvent { new_level: false });
+                    continue;
+                }
+                errors
+            }
+            Err(error) => vec![error],
+        };
+        let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        warn!("level {} is invalid, keeping the previous map: {}", index + 1, errors.join("; "));
+        if is_current {
+            hot_reload.errors = errors;
+        }
+    }
+}
+
+pub fn keep_player_position(
+    mut change_level_event: EventReader<ChangeLevelEvent>,
+    mut hot_reload: ResMut<HotReload>,
+    mut player: Query<(&mut Player, &mut Transform)>,
+) {
+    // les erreurs concernent la carte du niveau qu'on vient de quitter
+    if change_level_event.read().any(|event| event.new_level) {
+        hot_reload.errors.clear();
+    }
+    let Some((game_x, game_y)) = hot_reload.keep_player.take() else { return; };
+    let Ok((mut player, mut player_transform)) = player.get_single_mut() else { return; };
+    player_transform.translation = player.move_without_animation(game_x, game_y).extend(0.);
+}
+
+fn show_reload_errors(
+    mut commands: Commands,
+    hot_reload: Res<HotReload>,
+    texts: Query<Entity, With<ReloadErrorText>>,
+) {
+    if !hot_reload.is_changed() { return; }
+    for entity in texts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if hot_reload.errors.is_empty() { return; }
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(50.),
+                left: Val::Px(10.),
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            },
+            background_color: Color::rgba(0.4, 0., 0., 0.8).into(),
+            ..default()
+        },
+        ReloadErrorText,
+        InGame,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            format!("invalid level file, keeping the previous map:\n{}", hot_reload.errors.join("\n")),
+            TextStyle {
+                font_size: 18.,
+                color: Color::WHITE,
+                ..default()
+            },
+        ));
+    });
+}
+
+fn clear_reload_errors(mut hot_reload: ResMut<HotReload>) {
+    hot_reload.keep_player = None;
+    hot_reload.errors.clear();
+}
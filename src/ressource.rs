@@ -1,6 +0,0 @@
-use bevy::{ecs::system::Resource, math::Vec2};
-
-#[derive(Resource, Clone, Copy)]
-pub struct BeginClick {
-    pub position: Option<Vec2>
-}
\ No newline at end of file
@@ -0,0 +1,243 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use bevy::{app::{App, Plugin, Startup}, ecs::system::{Query, Res, Resource}, log::{info, warn}, window::{Window, WindowMode}};
+use serde::{Deserialize, Serialize};
+
+use crate::{Difficulty, TickMode, NB_LEVEL};
+
+/// Version actuelle du format de sauvegarde
+pub const SAVE_VERSION: u32 = 1;
+const SAVE_FILE: &str = "save.ron";
+/// Ancienne sauvegarde `clé=valeur` écrite à côté de l'exécutable (version 0)
+const LEGACY_SAVE_PATH: &str = "dungeon.save";
+
+pub struct SavePlugin;
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let storage: Box<dyn SaveStorage> = Box::new(FileStorage::in_data_dir());
+        #[cfg(target_arch = "wasm32")]
+        let storage: Box<dyn SaveStorage> = Box::new(MemoryStorage::default());
+
+        app
+            .insert_resource(SaveGame::load(storage))
+            .add_systems(Startup, apply_settings);
+    }
+}
+
+/// Endroit où la sauvegarde est lue et écrite.
+/// Le jeu web peut brancher son propre stockage sans toucher au reste.
+pub trait SaveStorage: Send + Sync + 'static {
+    fn read(&self) -> Option<String>;
+    fn write(&self, content: &str) -> Result<(), String>;
+}
+
+/// Fichier dans le dossier de données de la plateforme.
+pub struct FileStorage {
+    pub path: PathBuf,
+}
+
+impl FileStorage {
+    pub fn in_data_dir() -> FileStorage {
+        return FileStorage::named(SAVE_FILE);
+    }
+
+    /// Un autre fichier du dossier de données
+    pub fn named(file: &str) -> FileStorage {
+        return FileStorage { path: data_dir().join(file) };
+    }
+}
+
+impl SaveStorage for FileStorage {
+    fn read(&self) -> Option<String> {
+        return std::fs::read_to_string(&self.path).ok();
+    }
+
+    fn write(&self, content: &str) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+        }
+        return std::fs::write(&self.path, content).map_err(|error| error.to_string());
+    }
+}
+
+/// Sauvegarde gardée en mémoire, perdue à la fermeture du jeu.
+#[derive(Default)]
+pub struct MemoryStorage {
+    content: std::sync::Mutex<Option<String>>,
+}
+
+impl SaveStorage for MemoryStorage {
+    fn read(&self) -> Option<String> {
+        return self.content.lock().unwrap().clone();
+    }
+
+    fn write(&self, content: &str) -> Result<(), String> {
+        *self.content.lock().unwrap() = Some(content.to_owned());
+        return Ok(());
+    }
+}
+
+pub fn data_dir() -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+
+    if cfg!(target_os = "windows") {
+        if let Some(app_data) = std::env::var_os("APPDATA") {
+            return PathBuf::from(app_data).join("dungeon");
+        }
+    } else if cfg!(target_os = "macos") {
+        return home.join("Library/Application Support/dungeon");
+    } else if let Some(xdg_data) = std::env::var_os("XDG_DATA_HOME") {
+        return PathBuf::from(xdg_data).join("dungeon");
+    }
+    return home.join(".local/share/dungeon");
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub fullscreen: bool,
+    /// Coups gardés en attente pendant une animation, de 0 à `MAX_INPUT_BUFFER`
+    pub input_buffer: usize,
+    /// Mode des niveaux qui n'ont pas de ligne `mode=`
+    pub tick_mode: TickMode,
+    pub difficulty: Difficulty,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { music_volume: 0.7, sfx_volume: 1., fullscreen: false, input_buffer: 1, tick_mode: TickMode::Turns, difficulty: Difficulty::Normal }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct SaveData {
+    pub version: u32,
+    /// Plus grand niveau accessible, en commençant à 1
+    pub unlocked_level: usize,
+    pub best_scores: BTreeMap<usize, u32>,
+    pub best_moves: BTreeMap<usize, u32>,
+    /// Meilleur temps de chaque niveau, en secondes
+    pub best_times: BTreeMap<usize, f32>,
+    pub best_total: u32,
+    /// Pièces ramassées depuis le début
+    pub coins: u32,
+    /// Cases découvertes dans les niveaux avec brouillard, une lettre par case
+    pub explored: BTreeMap<usize, String>,
+    pub settings: Settings,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        SaveData {
+            version: SAVE_VERSION,
+            unlocked_level: 1,
+            best_scores: BTreeMap::new(),
+            best_moves: BTreeMap::new(),
+            best_times: BTreeMap::new(),
+            best_total: 0,
+            coins: 0,
+            explored: BTreeMap::new(),
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl SaveData {
+    /// Garde les records d'un niveau terminé et débloque le suivant.
+    pub fn record_level(&mut self, level: usize, score: u32, moves: u32, time: f32) {
+        let best_score = self.best_scores.entry(level).or_insert(score);
+        *best_score = (*best_score).max(score);
+        let best_moves = self.best_moves.entry(level).or_insert(moves);
+        *best_moves = (*best_moves).min(moves);
+        let best_time = self.best_times.entry(level).or_insert(time);
+        *best_time = best_time.min(time);
+
+        self.unlocked_level = self.unlocked_level.max((level + 1).min(NB_LEVEL as usize));
+    }
+
+    /// Lit une sauvegarde et la met à jour vers [`SAVE_VERSION`].
+    pub fn from_ron(content: &str) -> Result<SaveData, ron::error::SpannedError> {
+        let data: SaveData = ron::from_str(content)?;
+        return Ok(data.migrate());
+    }
+
+    pub fn to_ron(&self) -> String {
+        return ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap();
+    }
+
+    /// Met à jour une sauvegarde écrite par une version précédente du jeu.
+    fn migrate(mut self) -> SaveData {
+        if self.version == 0 {
+            // 0 → 1 : les champs ajoutés gardent leur valeur par défaut
+            self.version = 1;
+        }
+        return self;
+    }
+
+    /// Convertit l'ancienne sauvegarde `clé=valeur` (version 0).
+    pub fn from_legacy(content: &str) -> SaveData {
+        let mut data = SaveData::default();
+
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else { continue; };
+            let (key, value) = (key.trim(), value.trim());
+
+            if key == "best_total" {
+                data.best_total = value.parse().unwrap_or(0);
+            } else if let Some(level) = key.strip_prefix("best_score.").and_then(|level| level.parse().ok()) {
+                data.best_scores.insert(level, value.parse().unwrap_or(0));
+                data.unlocked_level = data.unlocked_level.max((level + 1).min(NB_LEVEL as usize));
+            } else if let Some(level) = key.strip_prefix("explored.").and_then(|level| level.parse().ok()) {
+                data.explored.insert(level, value.to_owned());
+            }
+        }
+        return data;
+    }
+}
+
+#[derive(Resource)]
+pub struct SaveGame {
+    pub data: SaveData,
+    storage: Box<dyn SaveStorage>,
+}
+
+impl SaveGame {
+    pub fn load(storage: Box<dyn SaveStorage>) -> SaveGame {
+        let data = match storage.read() {
+            Some(content) => SaveData::from_ron(&content).unwrap_or_else(|error| {
+                warn!("could not read save, starting a new one: {}", error);
+                SaveData::default()
+            }),
+            None => match std::fs::read_to_string(LEGACY_SAVE_PATH) {
+                Ok(content) => {
+                    info!("migrating legacy save {}", LEGACY_SAVE_PATH);
+                    SaveData::from_legacy(&content)
+                }
+                Err(_) => SaveData::default(),
+            },
+        };
+        return SaveGame { data, storage };
+    }
+
+    pub fn write(&self) {
+        if let Err(error) = self.storage.write(&self.data.to_ron()) {
+            warn!("could not write save: {}", error);
+        }
+    }
+}
+
+impl Settings {
+    pub fn window_mode(&self) -> WindowMode {
+        return if self.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed };
+    }
+}
+
+fn apply_settings(save: Res<SaveGame>, mut windows: Query<&mut Window>) {
+    for mut window in windows.iter_mut() {
+        window.mode = save.data.settings.window_mode();
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use bevy::{app::{App, Plugin, PreUpdate, Update}, asset::Assets, ecs::{entity::Entity, event::EventReader, schedule::{common_conditions::in_state, IntoSystemConfigs}, system::{Commands, Query, Res, ResMut, Resource}}, time::Time};
+
+use crate::*;
+
+const COIN_POINTS: u32 = 10;
+const PAR_BONUS: u32 = 50;
+
+pub struct ScorePlugin;
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Score>()
+            .init_resource::<RunStats>()
+            .add_systems(PreUpdate, count_deaths.before(start_transition).run_if(in_state(GameState::Game)))
+            .add_systems(Update, (
+                tick_level_time,
+                collect_coins,
+            ).run_if(in_state(GameState::Game)).run_if(in_state(PauseState::Running)))
+            .add_systems(Update, finish_level_score.before(change_level_event_listener).run_if(in_state(GameState::Game)));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Score {
+    /// Points gagnés dans le niveau en cours
+    pub level: u32,
+    /// Points des niveaux terminés
+    pub total: u32,
+    /// Mouvements du joueur dans le niveau en cours
+    pub moves: u32,
+    pub coins: u32,
+    pub total_coins: u32,
+    /// Temps passé dans le niveau en cours, en secondes
+    pub time: f32,
+}
+
+impl Score {
+    fn reset_level(&mut self) {
+        self.level = 0;
+        self.moves = 0;
+        self.coins = 0;
+        self.time = 0.;
+    }
+}
+
+/// Statistiques d'un niveau pendant la partie en cours
+#[derive(Clone, Default, Debug)]
+pub struct LevelStats {
+    /// Mouvements et temps de tous les essais, morts comprises
+    pub moves: u32,
+    pub time: f32,
+    pub deaths: u32,
+    pub coins: u32,
+    /// Mouvements et temps de l'essai réussi
+    pub completed_moves: Option<u32>,
+    pub completed_time: Option<f32>,
+    /// Records avant cette partie, pour comparer
+    pub previous_best_moves: Option<u32>,
+    pub previous_best_time: Option<f32>,
+}
+
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub levels: BTreeMap<usize, LevelStats>,
+    /// Fichier où la partie a été exportée
+    pub exported: Option<String>,
+}
+
+impl RunStats {
+    pub fn total_moves(&self) -> u32 {
+        return self.levels.values().map(|level| level.moves).sum();
+    }
+
+    pub fn total_time(&self) -> f32 {
+        return self.levels.values().map(|level| level.time).sum();
+    }
+
+    pub fn total_deaths(&self) -> u32 {
+        return self.levels.values().map(|level| level.deaths).sum();
+    }
+
+    pub fn total_coins(&self) -> u32 {
+        return self.levels.values().map(|level| level.coins).sum();
+    }
+
+    /// Une ligne par niveau terminé, comparée aux records
+    pub fn level_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (level, stats) in &self.levels {
+            let (Some(moves), Some(time)) = (stats.completed_moves, stats.completed_time) else { continue; };
+            let mut line = format!("Niveau {} : {} coups en {:.1} s, {} mort(s)", level, moves, time, stats.deaths);
+            match (stats.previous_best_moves, stats.previous_best_time) {
+                (Some(best_moves), Some(best_time)) => {
+                    line.push_str(&format!(" (record : {} coups, {:.1} s)", best_moves, best_time));
+                    if moves < best_moves || time < best_time {
+                        line.push_str(" - nouveau record !");
+                    }
+                }
+                _ => line.push_str(" - premier passage"),
+            }
+            lines.push(line);
+        }
+        return lines;
+    }
+
+    pub fn total_line(&self) -> String {
+        return format!(
+            "Total : {} coups, {:.1} s, {} mort(s), {} pièce(s)",
+            self.total_moves(),
+            self.total_time(),
+            self.total_deaths(),
+            self.total_coins(),
+        );
+    }
+}
+
+fn tick_level_time(time: Res<Time>, mut score: ResMut<Score>) {
+    score.time += time.delta_seconds();
+}
+
+fn collect_coins(
+    mut commands: Commands,
+    player: Query<&Player>,
+    coin_query: Query<(Entity, &Coin)>,
+    mut score: ResMut<Score>,
+) {
+    let player = player.single();
+    if player.game_x.is_none() || player.game_y.is_none() { return; }
+
+    for (entity, coin) in coin_query.iter() {
+        if coin.game_x == player.game_x.unwrap() && coin.game_y == player.game_y.unwrap() {
+            commands.entity(entity).despawn();
+            score.coins += 1;
+            score.level += COIN_POINTS;
+        }
+    }
+}
+
+/// Une seule mort par transition : les évènements suivants sont ignorés comme dans `start_transition`
+fn count_deaths(
+    mut level_end_event: EventReader<LevelEndEvent>,
+    transition: Res<LevelTransition>,
+    level_res: Res<CurrentLevel>,
+    mut run_stats: ResMut<RunStats>,
+) {
+    let Some(event) = level_end_event.read().last() else { return; };
+    if event.kind == LevelEnd::Died && transition.is_idle() {
+        run_stats.levels.entry(level_res.level).or_default().deaths += 1;
+    }
+}
+
+fn finish_level_score(
+    mut change_level_event: EventReader<ChangeLevelEvent>,
+    level_res: Res<CurrentLevel>,
+    level_maps: Res<LevelMaps>,
+    level_assets: Res<Assets<LevelAsset>>,
+    mut score: ResMut<Score>,
+    mut run_stats: ResMut<RunStats>,
+    mut save: ResMut<SaveGame>,
+) {
+    let Some(event) = change_level_event.read().last() else { return; };
+
+    let stats = run_stats.levels.entry(level_res.level).or_default();
+    stats.moves += score.moves;
+    stats.time += score.time;
+
+    if event.new_level {
+        stats.coins = score.coins;
+        stats.completed_moves = Some(score.moves);
+        stats.completed_time = Some(score.time);
+        stats.previous_best_moves = save.data.best_moves.get(&level_res.level).copied();
+        stats.previous_best_time = save.data.best_times.get(&level_res.level).copied();
+
+        let par = level_maps.current(&level_res, &level_assets).and_then(|level| level.par);
+        if par.is_some_and(|par| score.moves <= par) {
+            score.level += PAR_BONUS;
+        }
+
+        score.total += score.level;
+        score.total_coins += score.coins;
+
+        save.data.record_level(level_res.level, score.level, score.moves, score.time);
+        save.data.coins += score.coins;
+        if level_res.level == NB_LEVEL as usize {
+            save.data.best_total = save.data.best_total.max(score.total);
+        }
+        save.write();
+    }
+
+    score.reset_level();
+}
@@ -1,8 +1,8 @@
-use bevy::{math::{vec2, vec3}, prelude::*, sprite::Anchor};
+use bevy::{math::{vec2, vec3}, prelude::*};
 use bevy_pixel_camera::{PixelViewport, PixelZoom};
 use rand::Rng;
 
-use crate::{GameState, LevelMaps, StartButton, TexturesRessource, NB_LEVEL, RIGHT, TOP};
+use crate::{GameState, InGame, LevelMaps, TexturesRessource, NB_LEVEL, RIGHT, TOP};
 
 pub struct SetupPlugin;
 impl Plugin for SetupPlugin {
@@ -11,7 +11,6 @@ impl Plugin for SetupPlugin {
             .add_systems(OnEnter(GameState::Game), (
                 build_side_wall,
                 spawn_cloud,
-                spawn_start_buttons,
             ))
             .add_systems(Startup, (
                 load_level_maps,
@@ -22,7 +21,7 @@ impl Plugin for SetupPlugin {
 }
 
 fn spawn_camera(mut commands: Commands) {
-    println!("spawn camera");
+    debug!("spawning camera");
     commands.spawn((
         Camera2dBundle::default(),
         PixelZoom::FitSize {
@@ -33,44 +32,52 @@ fn spawn_camera(mut commands: Commands) {
     ));
 }
 
-fn load_level_maps(
+pub fn load_level_maps(
     asset_server: Res<AssetServer>,
     mut level_maps: ResMut<LevelMaps>,
 ) {
-    println!("load level map");
+    info!("loading {} level maps", NB_LEVEL);
     for index in 0..NB_LEVEL {
         level_maps.maps = Vec::new();
         level_maps.maps_handle.push(asset_server.load(format!("map/level-{}.lev", index+1)));
     }
 }
 
-fn load_entity_assets(
+pub fn load_entity_assets(
     asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     mut textures_ressource: ResMut<TexturesRessource>,
 ){
-    println!("load entity assets");
-    textures_ressource.player_center = asset_server.load("textures/entity/hero1.png");
-    textures_ressource.player_left = (asset_server.load("textures/entity/hero-left-1.png"), asset_server.load("textures/entity/hero-left-2.png"));
-    textures_ressource.player_right = (asset_server.load("textures/entity/hero-right-1.png"), asset_server.load("textures/entity/hero-right-2.png"));
-
-    textures_ressource.bat_left = (asset_server.load("textures/entity/left-bat-1.png"), asset_server.load("textures/entity/left-bat-2.png"));
-    textures_ressource.bat_right = (asset_server.load("textures/entity/right-bat-1.png"), asset_server.load("textures/entity/right-bat-2.png"));
-
-    textures_ressource.chest_open = (asset_server.load("textures/object/chest-2.png"), asset_server.load("textures/object/chest-3.png"), asset_server.load("textures/object/chest-4.png"))
+    debug!("loading sprite sheets");
+    let mut sheet = |path: &str, tile_size: Vec2, columns: usize| {
+        let image: Handle<Image> = asset_server.load(path.to_owned());
+        textures_ressource.sheets.push(image.clone());
+        return texture_atlases.add(TextureAtlas::from_grid(image, tile_size, columns, 1, None, None));
+    };
+
+    let player = sheet("textures/entity/hero-sheet.png", Vec2::new(16., 16.), 5);
+    let bat = sheet("textures/entity/bat-sheet.png", Vec2::new(32., 32.), 4);
+    let chest = sheet("textures/object/chest-sheet.png", Vec2::new(32., 32.), 4);
+    let torch = sheet("textures/object/torch-sheet.png", Vec2::new(16., 31.), 3);
+
+    textures_ressource.player = player;
+    textures_ressource.bat = bat;
+    textures_ressource.chest = chest;
+    textures_ressource.torch = torch;
 }
 
 fn build_side_wall(
     mut commands: Commands,
     asset_server: Res<AssetServer>
 ) {
-    println!("build side wall");
+    debug!("building side walls");
     let wall_tex = asset_server.load("textures/walls/dungeon-wall.png");
 
     for game_y in -2..20 {
-        commands.spawn(SpriteBundle { // left
+        commands.spawn((SpriteBundle { // left
                 texture: wall_tex.clone(),
                 transform: Transform {
-                    translation: vec3(25. +(-1*50-RIGHT) as f32, 25. + (game_y*50-TOP) as f32, 0.),
+                    translation: vec3(25. +(-50-RIGHT) as f32, 25. + (game_y*50-TOP) as f32, 0.),
                     ..default()
                 },
                 sprite: Sprite {
@@ -79,9 +86,9 @@ fn build_side_wall(
                     ..default()
                 },
                 ..default()
-        });
+        }, InGame));
 
-        commands.spawn(SpriteBundle { // right
+        commands.spawn((SpriteBundle { // right
             texture: wall_tex.clone(),
             transform: Transform {
                 translation: vec3(25. +(18*50-RIGHT) as f32, 25. + (game_y*50-TOP) as f32, 0.),
@@ -93,7 +100,7 @@ fn build_side_wall(
                 ..default()
             },
             ..default()
-        });
+        }, InGame));
     } 
 }
 
@@ -101,7 +108,7 @@ fn spawn_cloud(
     mut commands: Commands,
     asset_server: Res<AssetServer>
 ) {
-    println!("spawn cloud");
+    debug!("spawning clouds");
     let mut rng = rand::thread_rng();
 
     for for_x in 0..1000 {
@@ -111,10 +118,10 @@ fn spawn_cloud(
         for for_y in 0..100 {
             let y = for_y-430;
             
-            let spawning_chance = ({if for_y < 30 {0.003} else {0.0007}} + {if for_x < 95 || for_x > 905 {0.0005} else {0.003}}) / 4.;
+            let spawning_chance = ({if for_y < 30 {0.003} else {0.0007}} + {if !(95..=905).contains(&for_x) {0.0005} else {0.003}}) / 4.;
 
             if rng.gen::<f32>() < spawning_chance {
-                commands.spawn(SpriteBundle {
+                commands.spawn((SpriteBundle {
                     texture: asset_server.load("textures/decor/cloud.png"),
                     transform: Transform {
                         translation: vec3(x as f32, y as f32, 2.0),
@@ -125,7 +132,7 @@ fn spawn_cloud(
                         ..default()
                     },
                     ..default()
-                });
+                }, InGame));
             }
         }
 
@@ -133,10 +140,10 @@ fn spawn_cloud(
         for for_y in 0..100 {
             let y = for_y+350;
             
-            let spawning_chance = ({if for_y < 30 {0.003} else {0.0007}} + {if for_x < 95 || for_x > 905 {0.0005} else {0.003}}) / 4.;
+            let spawning_chance = ({if for_y < 30 {0.003} else {0.0007}} + {if !(95..=905).contains(&for_x) {0.0005} else {0.003}}) / 4.;
 
             if rng.gen::<f32>() < spawning_chance {
-                commands.spawn(SpriteBundle {
+                commands.spawn((SpriteBundle {
                     texture: asset_server.load("textures/decor/cloud.png"),
                     transform: Transform {
                         translation: vec3(x as f32, y as f32, 2.0),
@@ -147,31 +154,8 @@ fn spawn_cloud(
                         ..default()
                     },
                     ..default()
-                });
+                }, InGame));
             }
         }
     }
 }
-
-fn spawn_start_buttons(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-) {
-    println!("spawn start button");
-    commands.spawn((
-        SpriteBundle {
-            texture: asset_server.load("textures/decor/start-button.png"),
-            sprite: Sprite {
-                anchor: Anchor::BottomLeft,
-                ..Default::default()
-            },
-            transform: Transform {
-                translation: vec3(-260., -100., 5.),
-                scale: vec3(0.7, 0.7, 1.),
-                ..Default::default()
-            },
-            ..Default::default()
-        },
-        StartButton {},
-    ));
-}
\ No newline at end of file
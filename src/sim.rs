@@ -0,0 +1,461 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::utils::thiserror;
+use thiserror::Error;
+
+use crate::{SCREEN_GAME_X, SCREEN_GAME_Y};
+
+// Règles du jeu sans ECS, pour relire les niveaux sans fenêtre.
+// Elles suivent `move_player`, `tick_event_listener` et `end_tick_event_listener`, en tour par tour.
+
+/// Solution de référence d'un niveau, à côté du `.lev`, vérifiée par les tests
+pub const SOLUTION_EXTENSION: &str = "sol";
+/// Au-delà, le joueur tombe sans fin
+const SETTLE_LIMIT: usize = 200;
+/// Nombre d'états explorés avant d'abandonner une recherche
+const SEARCH_LIMIT: usize = 500_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tile {
+    Air,
+    Wall,
+    Player,
+    BlueDoor,
+    RedDoor,
+    Chest,
+    Coin,
+    Torch,
+}
+
+impl Tile {
+    pub fn from_char(tile: char) -> Option<Tile> {
+        return match tile {
+            '-' => Some(Tile::Air),
+            '1' => Some(Tile::Wall),
+            '&' => Some(Tile::Player),
+            'B' => Some(Tile::BlueDoor),
+            'R' => Some(Tile::RedDoor),
+            'C' => Some(Tile::Chest),
+            'o' => Some(Tile::Coin),
+            'T' => Some(Tile::Torch),
+            _ => None,
+        };
+    }
+
+    pub fn to_char(&self) -> char {
+        return match self {
+            Tile::Air => '-',
+            Tile::Wall => '1',
+            Tile::Player => '&',
+            Tile::BlueDoor => 'B',
+            Tile::RedDoor => 'R',
+            Tile::Chest => 'C',
+            Tile::Coin => 'o',
+            Tile::Torch => 'T',
+        };
+    }
+
+    /// Couleur ANSI de fond et de texte
+    fn ansi(&self) -> &'static str {
+        return match self {
+            Tile::Air => "\x1b[90m",
+            Tile::Wall => "\x1b[47;30m",
+            Tile::Player => "\x1b[1;32m",
+            Tile::BlueDoor => "\x1b[1;34m",
+            Tile::RedDoor => "\x1b[1;31m",
+            Tile::Chest => "\x1b[33m",
+            Tile::Coin => "\x1b[1;93m",
+            Tile::Torch => "\x1b[38;5;208m",
+        };
+    }
+}
+
+/// Problème dans une carte, les lignes et colonnes commencent à 1
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum LevelError {
+    #[error("line {line}, column {column}: unknown tile '{tile}'")]
+    UnknownTile { line: usize, column: usize, tile: char },
+    #[error("line {line}: {width} tiles instead of {expected}")]
+    Width { line: usize, width: usize, expected: usize },
+    #[error("{0} lines instead of {1}")]
+    Height(usize, usize),
+    #[error("{0} players instead of 1")]
+    PlayerCount(usize),
+    #[error("{0} red doors instead of 1")]
+    RedDoorCount(usize),
+    #[error("{0} blue doors, they work in pairs")]
+    BlueDoorCount(usize),
+    #[error("blue doors at {0:?} and {1:?} share a row or a column, they never teleport")]
+    AlignedBlueDoors((i32, i32), (i32, i32)),
+    #[error("unknown move '{0}', expected L, R or U")]
+    UnknownMove(char),
+}
+
+/// Carte d'un niveau, la première ligne est en haut de l'écran
+#[derive(Clone, Debug)]
+pub struct LevelGrid {
+    rows: Vec<Vec<Tile>>,
+}
+
+impl LevelGrid {
+    /// Lit la carte comme `change_level_event_listener` : les lignes sont séparées par des blancs
+    pub fn parse(map: &str) -> Result<LevelGrid, LevelError> {
+        let mut rows = Vec::new();
+        for (line, text) in map.split_whitespace().enumerate() {
+            let mut row = Vec::new();
+            for (column, tile) in text.chars().enumerate() {
+                match Tile::from_char(tile) {
+                    Some(tile) => row.push(tile),
+                    None => return Err(LevelError::UnknownTile { line: line+1, column: column+1, tile }),
+                }
+            }
+            rows.push(row);
+        }
+        return Ok(LevelGrid { rows });
+    }
+
+    pub fn height(&self) -> usize {
+        return self.rows.len();
+    }
+
+    /// Position en jeu de la case à la ligne `line` et à la colonne `column`, en partant de 0
+    fn game_position(&self, line: usize, column: usize) -> (i32, i32) {
+        return (column as i32, self.height() as i32 - 1 - line as i32);
+    }
+
+    /// Cases de ce type, en coordonnées de jeu, dans l'ordre de lecture
+    pub fn positions(&self, wanted: Tile) -> Vec<(i32, i32)> {
+        let mut positions = Vec::new();
+        for (line, row) in self.rows.iter().enumerate() {
+            for (column, tile) in row.iter().enumerate() {
+                if *tile == wanted {
+                    positions.push(self.game_position(line, column));
+                }
+            }
+        }
+        return positions;
+    }
+
+    /// Les murs de la douzième ligne continuent sous l'écran
+    pub fn walls(&self) -> HashSet<(i32, i32)> {
+        let mut walls = HashSet::new();
+        for (line, row) in self.rows.iter().enumerate() {
+            for (column, tile) in row.iter().enumerate() {
+                if *tile != Tile::Wall { continue; }
+                let (game_x, game_y) = self.game_position(line, column);
+                walls.insert((game_x, game_y));
+                if line == 11 {
+                    for i in 1..5 {
+                        walls.insert((game_x, game_y-i));
+                    }
+                }
+            }
+        }
+        return walls;
+    }
+
+    /// Taille de la carte et éléments que le jeu suppose présents
+    pub fn validate(&self) -> Vec<LevelError> {
+        let mut errors = Vec::new();
+        if self.height() != SCREEN_GAME_Y as usize {
+            errors.push(LevelError::Height(self.height(), SCREEN_GAME_Y as usize));
+        }
+        for (line, row) in self.rows.iter().enumerate() {
+            if row.len() != SCREEN_GAME_X as usize {
+                errors.push(LevelError::Width { line: line+1, width: row.len(), expected: SCREEN_GAME_X as usize });
+            }
+        }
+
+        let players = self.positions(Tile::Player).len();
+        if players != 1 {
+            errors.push(LevelError::PlayerCount(players));
+        }
+        let red_doors = self.positions(Tile::RedDoor).len();
+        if red_doors != 1 {
+            errors.push(LevelError::RedDoorCount(red_doors));
+        }
+        let blue_doors = self.positions(Tile::BlueDoor);
+        if !blue_doors.len().is_multiple_of(2) {
+            errors.push(LevelError::BlueDoorCount(blue_doors.len()));
+        }
+        for (index, door) in blue_doors.iter().enumerate() {
+            for other in &blue_doors[index+1..] {
+                if door.0 == other.0 || door.1 == other.1 {
+                    errors.push(LevelError::AlignedBlueDoors(*door, *other));
+                }
+            }
+        }
+        return errors;
+    }
+
+    /// Aperçu texte, avec les couleurs ANSI si `color`
+    pub fn render(&self, color: bool) -> String {
+        let mut text = String::new();
+        for row in &self.rows {
+            for tile in row {
+                if color {
+                    text.push_str(tile.ansi());
+                    text.push(tile.to_char());
+                    text.push_str("\x1b[0m");
+                } else {
+                    text.push(tile.to_char());
+                }
+            }
+            text.push('\n');
+        }
+        return text;
+    }
+}
+
+/// Action du joueur, écrite `L`, `R` ou `U` dans les solutions
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Move {
+    Left,
+    Right,
+    Use,
+}
+
+impl Move {
+    pub const ALL: [Move; 3] = [Move::Left, Move::Right, Move::Use];
+
+    pub fn to_char(&self) -> char {
+        return match self {
+            Move::Left => 'L',
+            Move::Right => 'R',
+            Move::Use => 'U',
+        };
+    }
+
+    pub fn from_char(letter: char) -> Result<Move, LevelError> {
+        return match letter {
+            'L' => Ok(Move::Left),
+            'R' => Ok(Move::Right),
+            'U' => Ok(Move::Use),
+            _ => Err(LevelError::UnknownMove(letter)),
+        };
+    }
+}
+
+/// Les blancs sont ignorés
+pub fn parse_moves(text: &str) -> Result<Vec<Move>, LevelError> {
+    return text.chars().filter(|letter| !letter.is_whitespace()).map(Move::from_char).collect();
+}
+
+pub fn format_moves(moves: &[Move]) -> String {
+    return moves.iter().map(|action| action.to_char()).collect();
+}
+
+/// État logique entre deux coups, le joueur est posé
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SimState {
+    pub player: (i32, i32),
+    /// Dans l'ordre de `Simulation::chests`
+    pub opened: Vec<bool>,
+    pub spawned: Vec<bool>,
+    /// Dans leur ordre d'apparition, qui est aussi leur ordre de jeu
+    pub monsters: Vec<(i32, i32)>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum StepResult {
+    Playing(SimState),
+    /// Porte rouge passée, tous les coffres ouverts
+    Completed,
+    Died,
+    /// Le joueur tombe sans jamais toucher le sol
+    EndlessFall,
+    /// Le coup ne fait rien : mur, ou rien à utiliser
+    Blocked,
+}
+
+pub struct Simulation {
+    walls: HashSet<(i32, i32)>,
+    blue_doors: Vec<(i32, i32)>,
+    red_door: Option<(i32, i32)>,
+    /// Triés comme dans `end_tick_event_listener`, pour faire apparaître les monstres dans le même ordre
+    pub chests: Vec<(i32, i32)>,
+    start: Option<(i32, i32)>,
+}
+
+impl Simulation {
+    pub fn new(grid: &LevelGrid) -> Simulation {
+        let mut chests = grid.positions(Tile::Chest);
+        chests.sort_by_key(|(game_x, game_y)| (*game_y, *game_x));
+        return Simulation {
+            walls: grid.walls(),
+            blue_doors: grid.positions(Tile::BlueDoor),
+            red_door: grid.positions(Tile::RedDoor).first().copied(),
+            chests,
+            start: grid.positions(Tile::Player).first().copied(),
+        };
+    }
+
+    /// Le joueur tombe sur le sol avant le premier coup
+    pub fn start(&self) -> StepResult {
+        let Some(player) = self.start else { return StepResult::Blocked; };
+        let closed = vec![false; self.chests.len()];
+        return self.settle(player, closed.clone(), closed, Vec::new());
+    }
+
+    pub fn step(&self, state: &SimState, action: Move) -> StepResult {
+        let (game_x, game_y) = state.player;
+        let mut opened = state.opened.clone();
+        let player = match action {
+            Move::Left | Move::Right => {
+                let next_x = if action == Move::Left { game_x-1 } else { game_x+1 };
+                if self.walls.contains(&(next_x, game_y)) { return StepResult::Blocked; }
+                // le joueur ne sort pas de l'écran, mais le coup compte
+                (next_x.clamp(0, SCREEN_GAME_X-1), game_y)
+            }
+            Move::Use => {
+                let teleport = self.blue_doors.contains(&state.player)
+                    .then(|| self.blue_doors.iter().find(|door| door.0 != game_x && door.1 != game_y))
+                    .flatten();
+                match teleport {
+                    Some(door) => *door,
+                    None => {
+                        if self.red_door == Some(state.player) && opened.iter().all(|is_open| *is_open) {
+                            return StepResult::Completed;
+                        }
+                        let Some(chest) = self.chests.iter().position(|chest| *chest == state.player) else { return StepResult::Blocked; };
+                        opened[chest] = true;
+                        state.player
+                    }
+                }
+            }
+        };
+
+        let mut monsters = state.monsters.clone();
+        self.move_monsters(&mut monsters, player.0);
+        return self.settle(player, opened, state.spawned.clone(), monsters);
+    }
+
+    /// Rejoue une suite de coups depuis le début, s'arrête à la fin du niveau
+    pub fn replay(&self, moves: &[Move]) -> (StepResult, usize) {
+        let mut result = self.start();
+        for (index, action) in moves.iter().enumerate() {
+            let StepResult::Playing(state) = &result else { return (result, index); };
+            result = match self.step(state, *action) {
+                // un coup sans effet est ignoré, comme en jeu
+                StepResult::Blocked => continue,
+                next => next,
+            };
+        }
+        return (result, moves.len());
+    }
+
+    /// Plus courte suite de coups qui termine le niveau
+    pub fn solve(&self) -> Option<Vec<Move>> {
+        let StepResult::Playing(start) = self.start() else { return None; };
+        let mut previous: HashMap<SimState, Option<(SimState, Move)>> = HashMap::new();
+        previous.insert(start.clone(), None);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(state) = queue.pop_front() {
+            if previous.len() > SEARCH_LIMIT { return None; }
+            for action in Move::ALL {
+                match self.step(&state, action) {
+                    StepResult::Completed => {
+                        let mut moves = vec![action];
+                        let mut current = &state;
+                        while let Some(Some((before, action))) = previous.get(current) {
+                            moves.push(*action);
+                            current = before;
+                        }
+                        moves.reverse();
+                        return Some(moves);
+                    }
+                    StepResult::Playing(next) if !previous.contains_key(&next) => {
+                        previous.insert(next.clone(), Some((state.clone(), action)));
+                        queue.push_back(next);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        return None;
+    }
+
+    /// Cases où le joueur peut se tenir entre deux coups
+    pub fn reachable_cells(&self) -> HashSet<(i32, i32)> {
+        let mut cells = HashSet::new();
+        let StepResult::Playing(start) = self.start() else { return cells; };
+        let mut seen = HashSet::from([start.clone()]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(state) = queue.pop_front() {
+            cells.insert(state.player);
+            if seen.len() > SEARCH_LIMIT { break; }
+            for action in Move::ALL {
+                if let StepResult::Playing(next) = self.step(&state, action) {
+                    if seen.insert(next.clone()) {
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        return cells;
+    }
+
+    /// Même règle que `tick_event_listener`, y compris l'arrêt après le premier monstre bloqué
+    fn move_monsters(&self, monsters: &mut [(i32, i32)], player_x: i32) {
+        let mut has_moved = vec![false; monsters.len()];
+        let mut ended = false;
+        while !ended && !monsters.is_empty() {
+            let monsters_pos = monsters.to_vec();
+            for index in 0..monsters.len() {
+                if !has_moved[index] {
+                    let (game_x, game_y) = monsters[index];
+                    let free = |next_x: i32| !self.walls.contains(&(next_x, game_y)) && !monsters_pos.contains(&(next_x, game_y));
+                    let mut next = None;
+                    if game_x > player_x && free(game_x-1) {
+                        next = Some((game_x-1, game_y));
+                    }
+                    if next.is_none() && game_x < player_x && free(game_x+1) {
+                        next = Some((game_x+1, game_y));
+                    }
+                    if let Some(next) = next {
+                        monsters[index] = next;
+                        has_moved[index] = true;
+                        break;
+                    }
+                }
+                ended = true;
+            }
+        }
+    }
+
+    /// Gravité, apparition des monstres et collisions, comme `end_tick_event_listener`
+    fn settle(&self, mut player: (i32, i32), opened: Vec<bool>, mut spawned: Vec<bool>, mut monsters: Vec<(i32, i32)>) -> StepResult {
+        for _ in 0..SETTLE_LIMIT {
+            let (game_x, mut game_y) = player;
+            let mut falling = false;
+            if !self.walls.contains(&(game_x, game_y-1)) && !monsters.contains(&(game_x, game_y-1)) {
+                game_y -= 1;
+                falling = true;
+            }
+            if game_y < -1 {
+                game_y = SCREEN_GAME_Y-1;
+                falling = true;
+            }
+            player = (game_x, game_y);
+
+            for (index, chest) in self.chests.iter().enumerate() {
+                if opened[index] && !spawned[index] && player != *chest {
+                    spawned[index] = true;
+                    monsters.push(*chest);
+                }
+            }
+            if monsters.contains(&player) {
+                return StepResult::Died;
+            }
+
+            if !falling {
+                return StepResult::Playing(SimState { player, opened, spawned, monsters });
+            }
+            // chaque case de chute est un tour
+            self.move_monsters(&mut monsters, game_x);
+        }
+        return StepResult::EndlessFall;
+    }
+}
@@ -19,32 +19,42 @@ pub struct RedDoor {
 }
 
 #[derive(Component)]
-pub struct StartButton {
+pub struct Coin {
+    pub game_x: i32,
+    pub game_y: i32
+}
+
+#[derive(Component)]
+pub struct Torch {
+    pub game_x: i32,
+    pub game_y: i32,
 }
 
+impl Torch {
+    pub fn new(game_x: i32, game_y: i32) -> Torch {
+        return Torch { game_x, game_y };
+    }
+}
+
+/// Tout ce qui appartient à la partie en cours, supprimé au retour au menu
+#[derive(Component)]
+pub struct InGame;
+
 #[derive(Component)]
 pub struct Chest {
     pub game_x: i32,
     pub game_y: i32,
     pub is_open: bool,
-    animation_index: i8,
     pub has_spawn: bool,
 }
 
 impl Chest {
+    #[allow(clippy::redundant_field_names)]
     pub fn new(game_x: i32, game_y: i32) -> Chest {
-        return Chest { game_x: game_x, game_y: game_y, is_open: false, has_spawn:false, animation_index:1}
+        return Chest { game_x: game_x, game_y: game_y, is_open: false, has_spawn:false}
     }
 
     pub fn open(&mut self) {
         self.is_open = true;
     }
-
-    pub fn animate(&mut self) -> Option<i8> {
-        if self.is_open && self.animation_index < 4 && chrono::Local::now().timestamp_millis() % 500 > 250 {
-            self.animation_index += 1; // Animation à améliorer !
-            return Some(self.animation_index);
-        }
-        return None;
-    }
 }
\ No newline at end of file
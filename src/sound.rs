@@ -0,0 +1,210 @@
+use bevy::{app::{App, Plugin, PreUpdate, Startup, Update}, asset::{AssetServer, Assets, Handle}, audio::{AudioBundle, AudioSink, AudioSinkPlayback, AudioSource, PlaybackSettings, Volume}, ecs::{entity::Entity, event::{Event, EventReader}, schedule::{IntoSystemConfigs, State}, system::{Commands, Query, Res, ResMut, Resource}}, time::{Real, Time}};
+
+use crate::*;
+
+/// Durée du fondu enchaîné entre deux musiques, en secondes
+const CROSSFADE_DURATION: f32 = 1.5;
+const MENU_MUSIC: &str = "menu";
+/// Musique des niveaux qui n'ont pas de ligne `music=`
+const DEFAULT_LEVEL_MUSIC: &str = "dungeon";
+
+pub struct SoundPlugin;
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<SoundEvent>()
+            .add_systems(Startup, load_sounds)
+            .add_systems(PreUpdate, level_end_sounds.before(start_transition))
+            .add_systems(Update, (
+                play_sounds,
+                choose_music,
+                crossfade_music,
+            ).chain());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Sfx {
+    Footstep,
+    Fall,
+    Land,
+    ChestOpen,
+    Teleport,
+    BatSpawn,
+    Death,
+    LevelComplete,
+}
+
+/// Joue un effet sonore au volume des effets
+#[derive(Event, Clone, Copy)]
+pub struct SoundEvent {
+    pub sfx: Sfx,
+}
+
+#[derive(Resource)]
+pub struct SoundsRessource {
+    pub footstep: Handle<AudioSource>,
+    pub fall: Handle<AudioSource>,
+    pub land: Handle<AudioSource>,
+    pub chest_open: Handle<AudioSource>,
+    pub teleport: Handle<AudioSource>,
+    pub bat_spawn: Handle<AudioSource>,
+    pub death: Handle<AudioSource>,
+    pub level_complete: Handle<AudioSource>,
+    /// Musiques par nom, chargées au démarrage
+    pub music: Vec<(String, Handle<AudioSource>)>,
+}
+
+impl SoundsRessource {
+    pub fn sfx(&self, sfx: Sfx) -> Handle<AudioSource> {
+        return match sfx {
+            Sfx::Footstep => self.footstep.clone(),
+            Sfx::Fall => self.fall.clone(),
+            Sfx::Land => self.land.clone(),
+            Sfx::ChestOpen => self.chest_open.clone(),
+            Sfx::Teleport => self.teleport.clone(),
+            Sfx::BatSpawn => self.bat_spawn.clone(),
+            Sfx::Death => self.death.clone(),
+            Sfx::LevelComplete => self.level_complete.clone(),
+        };
+    }
+
+    pub fn music(&self, name: &str) -> Option<Handle<AudioSource>> {
+        return self.music.iter().find(|(music_name, _)| music_name == name).map(|(_, handle)| handle.clone());
+    }
+
+    pub fn handles(&self) -> Vec<Handle<AudioSource>> {
+        let mut handles = vec![
+            self.footstep.clone(),
+            self.fall.clone(),
+            self.land.clone(),
+            self.chest_open.clone(),
+            self.teleport.clone(),
+            self.bat_spawn.clone(),
+            self.death.clone(),
+            self.level_complete.clone(),
+        ];
+        handles.extend(self.music.iter().map(|(_, handle)| handle.clone()));
+        return handles;
+    }
+}
+
+/// Une musique en cours, `fade` va de 0 (muette) à 1
+#[derive(Component)]
+pub struct MusicTrack {
+    pub name: String,
+    pub fade: f32,
+    pub fading_out: bool,
+}
+
+#[allow(clippy::redundant_field_names)]
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>, mut loading_assets: ResMut<LoadingAssets>) {
+    let music = [MENU_MUSIC, "dungeon", "cave"].iter()
+        .map(|name| (name.to_string(), asset_server.load(format!("audio/music/{}.wav", name))))
+        .collect();
+
+    let sounds = SoundsRessource {
+        footstep: asset_server.load("audio/sfx/footstep.wav"),
+        fall: asset_server.load("audio/sfx/fall.wav"),
+        land: asset_server.load("audio/sfx/land.wav"),
+        chest_open: asset_server.load("audio/sfx/chest.wav"),
+        teleport: asset_server.load("audio/sfx/teleport.wav"),
+        bat_spawn: asset_server.load("audio/sfx/bat.wav"),
+        death: asset_server.load("audio/sfx/death.wav"),
+        level_complete: asset_server.load("audio/sfx/level-complete.wav"),
+        music: music,
+    };
+    loading_assets.handles.extend(sounds.handles().into_iter().map(|handle| handle.untyped()));
+    commands.insert_resource(sounds);
+}
+
+/// Comme `start_transition`, seule la première fin de niveau compte
+fn level_end_sounds(
+    mut level_end_event: EventReader<LevelEndEvent>,
+    transition: Res<LevelTransition>,
+    mut sound_event: EventWriter<SoundEvent>,
+) {
+    let Some(event) = level_end_event.read().last() else { return; };
+    if !transition.is_idle() { return; }
+
+    sound_event.send(SoundEvent {
+        sfx: match event.kind {
+            LevelEnd::Completed => Sfx::LevelComplete,
+            LevelEnd::Died => Sfx::Death,
+        },
+    });
+}
+
+fn play_sounds(
+    mut commands: Commands,
+    mut sound_event: EventReader<SoundEvent>,
+    sounds: Res<SoundsRessource>,
+    save: Res<SaveGame>,
+) {
+    for event in sound_event.read() {
+        commands.spawn(AudioBundle {
+            source: sounds.sfx(event.sfx),
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_relative(save.data.settings.sfx_volume)),
+        });
+    }
+}
+
+/// Musique du menu hors des niveaux, sinon celle indiquée par le niveau
+#[allow(clippy::redundant_field_names)]
+fn choose_music(
+    mut commands: Commands,
+    game_state: Res<State<GameState>>,
+    level_res: Res<CurrentLevel>,
+    level_maps: Res<LevelMaps>,
+    level_assets: Res<Assets<LevelAsset>>,
+    sounds: Res<SoundsRessource>,
+    mut track_query: Query<&mut MusicTrack>,
+) {
+    let wanted = match game_state.get() {
+        GameState::Game => level_maps.current(&level_res, &level_assets)
+            .and_then(|level| level.music.clone())
+            .unwrap_or(DEFAULT_LEVEL_MUSIC.to_owned()),
+        _ => MENU_MUSIC.to_owned(),
+    };
+    if track_query.iter().any(|track| track.name == wanted && !track.fading_out) { return; }
+
+    for mut track in track_query.iter_mut() {
+        track.fading_out = true;
+    }
+    let Some(source) = sounds.music(&wanted) else {
+        warn!("unknown music: {}", wanted);
+        return;
+    };
+    // le volume réel est donné par `crossfade_music` dès que la musique démarre
+    commands.spawn((
+        AudioBundle {
+            source: source,
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new_relative(0.)),
+        },
+        MusicTrack { name: wanted, fade: 0., fading_out: false },
+    ));
+}
+
+/// Le temps réel continue pendant la pause, la musique aussi
+fn crossfade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    save: Res<SaveGame>,
+    mut track_query: Query<(Entity, &mut MusicTrack, Option<&AudioSink>)>,
+) {
+    let step = time.delta_seconds() / CROSSFADE_DURATION;
+    for (entity, mut track, sink) in track_query.iter_mut() {
+        if track.fading_out {
+            track.fade = (track.fade - step).max(0.);
+            if track.fade <= 0. {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        } else {
+            track.fade = (track.fade + step).min(1.);
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(track.fade * save.data.settings.music_volume);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use bevy::{app::{App, Plugin, Update}, asset::AssetServer, ecs::{event::{Event, EventReader, EventWriter}, query::With, schedule::{common_conditions::in_state, IntoSystemConfigs}, system::{Commands, ParamSet, Query, Res}}, math::Vec2, prelude::default, render::color::Color, sprite::{Sprite, SpriteBundle}, transform::components::Transform};
+use bevy::{app::{App, Plugin, Update}, ecs::{event::{Event, EventReader, EventWriter}, query::With, schedule::{common_conditions::in_state, IntoSystemConfigs}, system::{Commands, ParamSet, Query, Res}}, math::Vec2, prelude::default, render::color::Color, sprite::{SpriteSheetBundle, TextureAtlasSprite}, transform::components::Transform};
 
 use crate::*;
 
@@ -6,21 +6,148 @@ pub struct TickPlugin;
 impl Plugin for TickPlugin {
     fn build(&self, app: &mut App) {
         app
+            .init_resource::<SimulationState>()
+            // un tour complet dans un ordre fixe : les évènements envoyés sont lus dans la même image
+            .configure_sets(Update, (
+                TurnSet::Input,
+                TurnSet::Tick,
+                TurnSet::EndTick,
+                TurnSet::Animate,
+            ).chain())
             .add_systems(Update, (
-                tick_event_listener,
-                end_tick_event_listener,
-            ).run_if(in_state(GameState::Game)))
+                (tick_event_listener, count_ticks).chain().in_set(TurnSet::Tick),
+                end_tick_event_listener.in_set(TurnSet::EndTick),
+                // gravité, chauves-souris sorties et collisions du tour comprises
+                hash_simulation_state.after(TurnSet::EndTick).before(TurnSet::Animate),
+            ).run_if(in_state(GameState::Game)).run_if(in_state(PauseState::Running)))
+            .add_systems(Update, reset_simulation_state.after(change_level_event_listener).run_if(in_state(GameState::Game)))
             .add_event::<TickEvent>()
             .add_event::<EndTickEvent>();
     }
 }
 
+/// Étapes d'un tour de jeu
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TurnSet {
+    /// Action du joueur ou horloge du temps réel, envoie `TickEvent`
+    Input,
+    /// Déplacement des monstres
+    Tick,
+    /// Gravité, apparition des chauves-souris et collisions
+    EndTick,
+    /// Déplacement des sprites vers leur case, envoie `EndTickEvent`
+    Animate,
+}
+
 #[derive(Event)]
 pub struct TickEvent;
 
+/// Compteur de tours et empreinte de l'état logique, pour comparer deux parties
+#[derive(Resource, Default, Debug)]
+pub struct SimulationState {
+    pub tick: u64,
+    pub hash: u64,
+    /// Ordre d'apparition du prochain monstre, les monstres jouent dans cet ordre
+    pub next_monster: u32,
+    /// Le joueur tombait à la fin du tour précédent, pour ne jouer qu'un son de chute
+    pub falling: bool,
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// FNV-1a : stable entre les exécutions et les plateformes, contrairement au `DefaultHasher`
+pub struct StateHasher {
+    hash: u64,
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        StateHasher { hash: FNV_OFFSET }
+    }
+}
+
+impl StateHasher {
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= *byte as u64;
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn finish(&self) -> u64 {
+        return self.hash;
+    }
+}
+
+fn reset_simulation_state(
+    mut change_level_event: EventReader<ChangeLevelEvent>,
+    mut simulation: ResMut<SimulationState>,
+) {
+    if change_level_event.read().last().is_none() { return; }
+    *simulation = SimulationState::default();
+}
+
+fn count_ticks(mut tick_event: EventReader<TickEvent>, mut simulation: ResMut<SimulationState>) {
+    simulation.tick += tick_event.read().count() as u64;
+}
+
+/// Calculé à la fin de chaque tour, une fois `end_tick_event_listener` passé
+fn hash_simulation_state(
+    mut end_tick_event: EventReader<EndTickEvent>,
+    mut simulation: ResMut<SimulationState>,
+    player: Query<&Player>,
+    monster_query: Query<&Monster>,
+    chest_query: Query<&Chest>,
+    coin_query: Query<&Coin>,
+) {
+    if end_tick_event.read().last().is_none() { return; }
+    let Ok(player) = player.get_single() else { return; };
+
+    let mut hasher = StateHasher::default();
+    hasher.write_u64(simulation.tick);
+    hasher.write_i32(player.game_x.unwrap_or(-1));
+    hasher.write_i32(player.game_y.unwrap_or(-1));
+
+    let mut monsters: Vec<(u32, i32, i32)> = monster_query.iter().map(|monster| (monster.order(), monster.game_x(), monster.game_y())).collect();
+    monsters.sort();
+    for (order, game_x, game_y) in monsters {
+        hasher.write_i32(order as i32);
+        hasher.write_i32(game_x);
+        hasher.write_i32(game_y);
+    }
+
+    let mut chests: Vec<(i32, i32, bool, bool)> = chest_query.iter().map(|chest| (chest.game_y, chest.game_x, chest.is_open, chest.has_spawn)).collect();
+    chests.sort();
+    for (game_y, game_x, is_open, has_spawn) in chests {
+        hasher.write_i32(game_x);
+        hasher.write_i32(game_y);
+        hasher.write_bytes(&[is_open as u8, has_spawn as u8]);
+    }
+
+    let mut coins: Vec<(i32, i32)> = coin_query.iter().map(|coin| (coin.game_y, coin.game_x)).collect();
+    coins.sort();
+    for (game_y, game_x) in coins {
+        hasher.write_i32(game_x);
+        hasher.write_i32(game_y);
+    }
+
+    simulation.hash = hasher.finish();
+    debug!("tick {} state hash {:016x}", simulation.tick, simulation.hash);
+}
+
+#[allow(clippy::bool_comparison)]
 pub fn tick_event_listener(
     mut events: ParamSet<(EventReader<TickEvent>, EventWriter<TickEvent>)>,
-    mut monsters: Query<&mut Monster>,
+    mut monsters: Query<(Entity, &mut Monster)>,
     wall_query: Query<&Wall>,
     player: Query<&Player>,
 ) {
@@ -30,23 +157,32 @@ pub fn tick_event_listener(
     let player_game_x = player.game_x.unwrap();
 
     { // move monster
-        for mut monster in monsters.iter_mut() {
+        for (_, mut monster) in monsters.iter_mut() {
             monster.has_moved = false; // tous les monstres n'ont pas encore bougé
         }
 
+        // l'ordre des requêtes ECS n'est pas garanti, les monstres jouent dans leur ordre d'apparition
+        let mut turn_order: Vec<(u32, Entity)> = monsters.iter().map(|(entity, monster)| (monster.order(), entity)).collect();
+        turn_order.sort();
+
+        let _span = debug_span!("tick", monsters = turn_order.len()).entered();
         let mut ended = false;
-        while ended == false && monsters.iter().len() != 0{
-            println!("while ended == false:");
+        let mut pass = 0;
+        while ended == false && !turn_order.is_empty() {
+            pass += 1;
+            trace!("monster pass {}", pass);
             let monsters_pos = { // liste statique de toutes les positions des monstres
                 let mut monsters_pos: Vec<(i32, i32)> = Vec::new();
-                monsters.iter().for_each(|e| {monsters_pos.push((e.game_x(), e.game_y()));});
+                monsters.iter().for_each(|(_, e)| {monsters_pos.push((e.game_x(), e.game_y()));});
                 monsters_pos
             };
 
-            for mut monster in monsters.iter_mut() {
+            for (_, entity) in turn_order.iter() {
+                let Ok((_, mut monster)) = monsters.get_mut(*entity) else { continue; };
                 if !monster.has_moved {
                     let has_moved = move_monster(monster.as_mut(), player_game_x, &wall_query, &monsters_pos);
                     if has_moved {
+                        trace!("monster #{} moves to {} {}", monster.order(), monster.game_x(), monster.game_y());
                         monster.has_moved = true;
                         break;
                     }
@@ -61,7 +197,7 @@ fn move_monster(
     monster: &mut Monster,
     player_game_x: i32,
     wall_query: &Query<'_, '_, &Wall>, 
-    monsters_pos: &Vec<(i32, i32)>
+    monsters_pos: &[(i32, i32)]
 ) -> bool {
     if monster.game_x() > player_game_x {
         // vérifier qu'il n'y a pas de murs
@@ -109,15 +245,20 @@ fn move_monster(
 #[derive(Event)]
 pub struct EndTickEvent;
 
+#[allow(clippy::bool_comparison, clippy::too_many_arguments, clippy::unnecessary_unwrap)]
 pub fn end_tick_event_listener(
-    mut events: ParamSet<(EventReader<EndTickEvent>, EventWriter<TickEvent>, EventWriter<ChangeLevelEvent>)>,
+    mut events: ParamSet<(EventReader<EndTickEvent>, EventWriter<TickEvent>, EventWriter<LevelEndEvent>)>,
     mut player: Query<&mut Player>,
     mut player_transform: Query<&mut Transform, With<Player>>,
     monster_query: Query<&Monster>,
     wall_query: Query<&Wall>,
     mut chest_query: Query<&mut Chest>,
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    textures_ressource: Res<TexturesRessource>,
+    mut sound_event: EventWriter<SoundEvent>,
+    mut simulation: ResMut<SimulationState>,
+    clock: Res<TickClock>,
+    #[cfg(feature = "dev")] cheats: Res<DevCheats>,
 ) {
     if events.p0().read().last().is_none() { return; }
 
@@ -143,9 +284,23 @@ pub fn end_tick_event_listener(
             }
         }
 
+        #[cfg(feature = "dev")]
+        if cheats.noclip {
+            is_something_under_player = true;
+        }
+
         if is_something_under_player == false {
+            if !simulation.falling {
+                sound_event.send(SoundEvent { sfx: Sfx::Fall });
+            }
+            simulation.falling = true;
             player.move_with_direction(Direction::Bottom);
-            events.p1().send(TickEvent);
+            if clock.is_turn_based() {
+                events.p1().send(TickEvent);
+            }
+        } else if simulation.falling {
+            simulation.falling = false;
+            sound_event.send(SoundEvent { sfx: Sfx::Land });
         }
     }
 
@@ -161,7 +316,9 @@ pub fn end_tick_event_listener(
 
         if player_game_y < -1 {
             player_transform.translation = player.move_without_animation(player_game_x, SCREEN_GAME_Y-1).extend(0.);
-            events.p1().send(TickEvent);
+            if clock.is_turn_based() {
+                events.p1().send(TickEvent);
+            }
         }
     }
 
@@ -170,40 +327,55 @@ pub fn end_tick_event_listener(
         if player.game_x.is_none() || player.game_y.is_none() || player.is_animating { return; }
 
         if player.game_x.is_some() && player.game_y.is_some() {
-            for mut chest in chest_query.iter_mut() {
+            // même ordre à chaque partie si plusieurs coffres libèrent leur monstre en même temps
+            let mut chests: Vec<Mut<Chest>> = chest_query.iter_mut().collect();
+            chests.sort_by_key(|chest| (chest.game_y, chest.game_x));
+            for mut chest in chests {
                 if chest.is_open && chest.has_spawn == false && !(player.game_x.unwrap() == chest.game_x && player.game_y.unwrap() == chest.game_y) {
                     chest.has_spawn = true;
-
-                    let monster_tex = asset_server.load("textures/entity/left-bat-1.png");
-                    commands.spawn((
-                        SpriteBundle {
-                            texture: monster_tex,
-                            transform: Transform {
-                                translation: Monster::get_translation(chest.game_x, chest.game_y).extend(0.),
-                                ..default()
-                            },
-                            sprite: Sprite {
-                                color: Color::rgb(1., 1., 1.),
-                                custom_size: Some(Vec2::new(45., 45.,)),
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        Monster::new(chest.game_x, chest.game_y),
-                    ));
-
+                    let order = simulation.next_monster;
+                    simulation.next_monster += 1;
+                    debug!("bat #{} leaves the chest at {} {}", order, chest.game_x, chest.game_y);
+                    sound_event.send(SoundEvent { sfx: Sfx::BatSpawn });
+                    spawn_bat(&mut commands, &textures_ressource, chest.game_x, chest.game_y, order);
                 }
             }
         }
     }
 
+    #[cfg(feature = "dev")]
+    if cheats.god { return; }
+
     { // Regarder si le monstre percute le joueur
         let player = player.single();
         if player.game_x.is_none() || player.game_y.is_none() { return; }
         for monster in monster_query.iter() {
             if monster.game_x() == player.game_x.unwrap() && monster.game_y() == player.game_y.unwrap() {
-                events.p2().send(ChangeLevelEvent { new_level: false });
+                debug!("player caught by monster #{} at {} {}", monster.order(), monster.game_x(), monster.game_y());
+                events.p2().send(LevelEndEvent { kind: LevelEnd::Died });
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// `order` : ordre d'apparition, pris dans `SimulationState::next_monster`
+pub fn spawn_bat(commands: &mut Commands, textures_ressource: &TexturesRessource, game_x: i32, game_y: i32, order: u32) {
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: textures_ressource.bat.clone(),
+            transform: Transform {
+                translation: Monster::get_translation(game_x, game_y).extend(0.),
+                ..default()
+            },
+            sprite: TextureAtlasSprite {
+                color: Color::rgb(1., 1., 1.),
+                custom_size: Some(Vec2::new(45., 45.,)),
+                ..default()
+            },
+            ..default()
+        },
+        Monster::new(game_x, game_y, order),
+        bat_animation(),
+        InGame,
+    ));
+}
@@ -0,0 +1,210 @@
+use bevy::{app::{App, Plugin, PreUpdate, Startup, Update}, ecs::{event::{Event, EventReader, EventWriter}, query::With, schedule::{common_conditions::in_state, IntoSystemConfigs, OnEnter}, system::{Commands, Query, Res, ResMut, Resource}}, render::{camera::Camera, color::Color}, time::Time, transform::components::GlobalTransform, ui::{node_bundles::NodeBundle, BackgroundColor, PositionType, Style, Val, ZIndex}, window::{PrimaryWindow, Window}};
+
+use crate::*;
+
+/// Durée d'une moitié de transition (fermeture ou ouverture), en secondes
+const TRANSITION_DURATION: f32 = 0.4;
+
+pub struct TransitionPlugin;
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<LevelTransition>()
+            .add_event::<LevelEndEvent>()
+            .add_systems(Startup, spawn_transition_overlay)
+            .add_systems(OnEnter(GameState::Game), start_opening)
+            .add_systems(OnEnter(GameState::Menu), reset_transition)
+            // avant `Update` pour que tous les lecteurs de `ChangeLevelEvent` le voient dans la même frame,
+            // et sans condition d'état car l'ouverture continue sur l'écran de fin
+            .add_systems(PreUpdate, (
+                start_transition.run_if(in_state(GameState::Game)),
+                advance_transition,
+            ).chain())
+            .add_systems(Update, draw_transition);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LevelEnd {
+    Completed,
+    Died,
+}
+
+/// Fin d'un niveau par le jeu, le changement de niveau attend la fin de la fermeture
+#[derive(Event, Clone, Copy)]
+pub struct LevelEndEvent {
+    pub kind: LevelEnd,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TransitionStyle {
+    /// Fondu au noir sur tout l'écran
+    Fade,
+    /// Un carré qui se referme sur le joueur
+    Iris,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TransitionPhase {
+    Idle,
+    Closing(LevelEnd),
+    Opening,
+}
+
+#[derive(Resource)]
+pub struct LevelTransition {
+    phase: TransitionPhase,
+    style: TransitionStyle,
+    /// 0 : écran visible, 1 : écran caché
+    progress: f32,
+}
+
+impl Default for LevelTransition {
+    fn default() -> Self {
+        LevelTransition { phase: TransitionPhase::Idle, style: TransitionStyle::Fade, progress: 0. }
+    }
+}
+
+impl LevelTransition {
+    pub fn is_idle(&self) -> bool {
+        return self.phase == TransitionPhase::Idle;
+    }
+}
+
+/// Le joueur ne peut pas bouger pendant une transition
+pub fn transition_idle(transition: Res<LevelTransition>) -> bool {
+    return transition.is_idle();
+}
+
+#[derive(Component, Clone, Copy)]
+enum IrisSide {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+#[derive(Component)]
+struct FadeOverlay;
+
+fn spawn_transition_overlay(mut commands: Commands) {
+    let overlay_style = Style {
+        position_type: PositionType::Absolute,
+        width: Val::Percent(100.),
+        height: Val::Percent(100.),
+        ..default()
+    };
+    commands.spawn((
+        NodeBundle {
+            style: overlay_style.clone(),
+            background_color: Color::rgba(0., 0., 0., 0.).into(),
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+        FadeOverlay,
+    ));
+    for side in [IrisSide::Top, IrisSide::Bottom, IrisSide::Left, IrisSide::Right] {
+        commands.spawn((
+            NodeBundle {
+                style: Style { width: Val::Px(0.), height: Val::Px(0.), ..overlay_style.clone() },
+                background_color: Color::BLACK.into(),
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            side,
+        ));
+    }
+}
+
+fn start_opening(mut transition: ResMut<LevelTransition>) {
+    transition.phase = TransitionPhase::Opening;
+    transition.style = TransitionStyle::Fade;
+    transition.progress = 1.;
+}
+
+fn reset_transition(mut transition: ResMut<LevelTransition>) {
+    *transition = LevelTransition::default();
+}
+
+pub fn start_transition(
+    mut level_end_event: EventReader<LevelEndEvent>,
+    mut transition: ResMut<LevelTransition>,
+) {
+    let Some(event) = level_end_event.read().last() else { return; };
+    if !transition.is_idle() { return; }
+
+    transition.phase = TransitionPhase::Closing(event.kind);
+    transition.style = match event.kind {
+        LevelEnd::Completed => TransitionStyle::Fade,
+        LevelEnd::Died => TransitionStyle::Iris,
+    };
+    transition.progress = 0.;
+}
+
+fn advance_transition(
+    time: Res<Time>,
+    mut transition: ResMut<LevelTransition>,
+    mut change_level_event: EventWriter<ChangeLevelEvent>,
+) {
+    let step = time.delta_seconds() / TRANSITION_DURATION;
+    match transition.phase {
+        TransitionPhase::Idle => (),
+        TransitionPhase::Closing(kind) => {
+            transition.progress = (transition.progress + step).min(1.);
+            if transition.progress >= 1. {
+                // l'écran est noir, on peut reconstruire le niveau
+                change_level_event.send(ChangeLevelEvent { new_level: kind == LevelEnd::Completed });
+                transition.phase = TransitionPhase::Opening;
+            }
+        }
+        TransitionPhase::Opening => {
+            transition.progress = (transition.progress - step).max(0.);
+            if transition.progress <= 0. {
+                transition.phase = TransitionPhase::Idle;
+            }
+        }
+    }
+}
+
+fn draw_transition(
+    transition: Res<LevelTransition>,
+    mut fade_query: Query<&mut BackgroundColor, With<FadeOverlay>>,
+    mut iris_query: Query<(&IrisSide, &mut Style)>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+) {
+    let fade = if transition.style == TransitionStyle::Fade { transition.progress } else { 0. };
+    for mut background in fade_query.iter_mut() {
+        background.0.set_a(fade);
+    }
+
+    let Ok(window) = windows.get_single() else { return; };
+    let (width, height) = (window.width(), window.height());
+    let iris = if transition.style == TransitionStyle::Iris { transition.progress } else { 0. };
+
+    // centre de l'iris : le joueur (ancré en bas à gauche), ou le milieu de l'écran
+    let center = camera_query.get_single().ok()
+        .zip(player_query.get_single().ok())
+        .and_then(|((camera, camera_transform), player_transform)| {
+            camera.world_to_viewport(camera_transform, player_transform.translation() + Vec3::new(16., 16., 0.))
+        })
+        .unwrap_or(Vec2::new(width / 2., height / 2.));
+    let half_size = (1. - iris) * width.max(height);
+    let (left, right) = ((center.x - half_size).max(0.), (center.x + half_size).min(width));
+    let (top, bottom) = ((center.y - half_size).max(0.), (center.y + half_size).min(height));
+
+    for (side, mut style) in iris_query.iter_mut() {
+        let (x, y, w, h) = match side {
+            IrisSide::Top => (0., 0., width, top),
+            IrisSide::Bottom => (0., bottom, width, height - bottom),
+            IrisSide::Left => (0., top, left, bottom - top),
+            IrisSide::Right => (right, top, width - right, bottom - top),
+        };
+        let visible = iris > 0.;
+        style.left = Val::Px(x);
+        style.top = Val::Px(y);
+        style.width = Val::Px(if visible { w.max(0.) } else { 0. });
+        style.height = Val::Px(if visible { h.max(0.) } else { 0. });
+    }
+}
@@ -0,0 +1,188 @@
+use bevy::{app::{App, Plugin, Update}, asset::{AssetServer, Handle}, ecs::{entity::Entity, event::{Event, EventReader, EventWriter}, query::{With, Without}, schedule::{common_conditions::in_state, IntoSystemConfigs}, system::{Commands, Query, Res, ResMut, Resource}}, transform::components::Transform};
+
+use crate::*;
+
+/// Nombre de coups qu'on peut annuler
+const UNDO_LIMIT: usize = 200;
+
+pub struct UndoPlugin;
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<UndoHistory>()
+            .add_event::<UndoEvent>()
+            .add_systems(Update, (
+                record_stable_state,
+                undo_restart_input,
+                apply_undo,
+            ).chain().before(move_player).in_set(TurnSet::Input).run_if(in_state(GameState::Game)).run_if(in_state(PauseState::Running)).run_if(transition_idle))
+            .add_systems(Update, clear_history.after(change_level_event_listener).run_if(in_state(GameState::Game)));
+    }
+}
+
+/// Revenir à l'état d'avant le dernier coup
+#[derive(Event, Clone, Copy)]
+pub struct UndoEvent;
+
+/// État logique d'un niveau quand le joueur peut jouer
+#[derive(Clone, PartialEq, Debug)]
+pub struct Snapshot {
+    pub player: (i32, i32),
+    /// (ordre d'apparition, x, y)
+    pub monsters: Vec<(u32, i32, i32)>,
+    /// (x, y, ouvert, monstre sorti)
+    pub chests: Vec<(i32, i32, bool, bool)>,
+    pub coins: Vec<(i32, i32)>,
+    pub moves: u32,
+    pub coins_collected: u32,
+    pub level_points: u32,
+    pub tick: u64,
+    pub next_monster: u32,
+}
+
+#[derive(Resource, Default)]
+pub struct UndoHistory {
+    /// Dernier état stable, pas encore dans l'historique
+    current: Option<Snapshot>,
+    snapshots: Vec<Snapshot>,
+}
+
+impl UndoHistory {
+    pub fn len(&self) -> usize {
+        return self.snapshots.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.snapshots.is_empty();
+    }
+}
+
+/// Un état est stable quand le joueur est posé et ne bouge plus : chaque coup, chute comprise, est une étape
+#[allow(clippy::too_many_arguments)]
+fn record_stable_state(
+    mut history: ResMut<UndoHistory>,
+    player: Query<&Player>,
+    monster_query: Query<&Monster>,
+    chest_query: Query<&Chest>,
+    coin_query: Query<&Coin>,
+    wall_query: Query<&Wall>,
+    score: Res<Score>,
+    simulation: Res<SimulationState>,
+) {
+    let Ok(player) = player.get_single() else { return; };
+    let (Some(game_x), Some(game_y)) = (player.game_x, player.game_y) else { return; };
+    if player.is_animating { return; }
+    if !player.on_the_ground(wall_query.iter(), monster_query.iter()) { return; }
+
+    let mut monsters: Vec<(u32, i32, i32)> = monster_query.iter().map(|monster| (monster.order(), monster.game_x(), monster.game_y())).collect();
+    monsters.sort();
+    let mut chests: Vec<(i32, i32, bool, bool)> = chest_query.iter().map(|chest| (chest.game_x, chest.game_y, chest.is_open, chest.has_spawn)).collect();
+    chests.sort();
+    let mut coins: Vec<(i32, i32)> = coin_query.iter().map(|coin| (coin.game_x, coin.game_y)).collect();
+    coins.sort();
+
+    let snapshot = Snapshot {
+        player: (game_x, game_y),
+        monsters,
+        chests,
+        coins,
+        moves: score.moves,
+        coins_collected: score.coins,
+        level_points: score.level,
+        tick: simulation.tick,
+        next_monster: simulation.next_monster,
+    };
+    if history.current.as_ref() == Some(&snapshot) { return; }
+
+    if let Some(previous) = history.current.replace(snapshot) {
+        history.snapshots.push(previous);
+        if history.snapshots.len() > UNDO_LIMIT {
+            history.snapshots.remove(0);
+        }
+    }
+}
+
+fn undo_restart_input(
+    actions: Res<ActionState>,
+    mut queue: ResMut<InputQueue>,
+    mut undo_event: EventWriter<UndoEvent>,
+    mut change_level_event: EventWriter<ChangeLevelEvent>,
+) {
+    if actions.just_pressed(Action::Undo) {
+        // annuler d'abord les coups en attente
+        if queue.is_empty() {
+            undo_event.send(UndoEvent);
+        } else {
+            queue.clear();
+        }
+    }
+    if actions.just_pressed(Action::Restart) {
+        change_level_event.send(ChangeLevelEvent { new_level: false });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_undo(
+    mut commands: Commands,
+    mut undo_event: EventReader<UndoEvent>,
+    mut history: ResMut<UndoHistory>,
+    mut player: Query<(&mut Player, &mut Transform)>,
+    mut monster_query: Query<(Entity, &mut Monster, &mut Transform), Without<Player>>,
+    mut chest_query: Query<(&mut Chest, &mut SpriteAnimation)>,
+    coin_query: Query<Entity, With<Coin>>,
+    mut score: ResMut<Score>,
+    mut simulation: ResMut<SimulationState>,
+    asset_server: Res<AssetServer>,
+) {
+    if undo_event.read().last().is_none() { return; }
+    let Some(snapshot) = history.snapshots.pop() else { return; };
+    // l'état annulé ne doit pas revenir dans l'historique
+    history.current = Some(snapshot.clone());
+
+    let (mut player, mut player_transform) = player.single_mut();
+    player_transform.translation = player.move_without_animation(snapshot.player.0, snapshot.player.1).extend(1.);
+
+    for (entity, mut monster, mut monster_transform) in monster_query.iter_mut() {
+        match snapshot.monsters.iter().find(|(order, _, _)| *order == monster.order()) {
+            Some((_, game_x, game_y)) => {
+                monster.set_position(*game_x, *game_y);
+                monster_transform.translation = Monster::get_translation(*game_x, *game_y).extend(0.5);
+            }
+            // sorti d'un coffre après cet état
+            None => commands.entity(entity).despawn(),
+        }
+    }
+
+    for (mut chest, mut chest_animation) in chest_query.iter_mut() {
+        let Some((_, _, is_open, has_spawn)) = snapshot.chests.iter().find(|(game_x, game_y, _, _)| *game_x == chest.game_x && *game_y == chest.game_y) else { continue; };
+        chest.is_open = *is_open;
+        chest.has_spawn = *has_spawn;
+        if !chest.is_open {
+            chest_animation.play("closed");
+        }
+    }
+
+    for entity in coin_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    let coin_tex: Handle<Image> = asset_server.load("textures/object/yellow.png");
+    for (game_x, game_y) in &snapshot.coins {
+        spawn_coin(&mut commands, coin_tex.clone(), *game_x, *game_y);
+    }
+
+    score.moves = snapshot.moves;
+    score.coins = snapshot.coins_collected;
+    score.level = snapshot.level_points;
+    simulation.tick = snapshot.tick;
+    simulation.next_monster = snapshot.next_monster;
+    // les états gardés ont le joueur posé
+    simulation.falling = false;
+}
+
+fn clear_history(
+    mut change_level_event: EventReader<ChangeLevelEvent>,
+    mut history: ResMut<UndoHistory>,
+) {
+    if change_level_event.read().last().is_none() { return; }
+    *history = UndoHistory::default();
+}
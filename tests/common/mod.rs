@@ -0,0 +1,155 @@
+//! Jeu sans fenêtre pour les tests : `MinimalPlugins`, le niveau, le joueur et les tours.
+
+#![allow(dead_code, clippy::needless_return)]
+
+use std::time::Duration;
+
+use bevy::{asset::AssetPlugin, ecs::{event::ManualEventReader, system::RunSystemOnce}, prelude::*, time::TimeUpdateStrategy};
+use dungeon::*;
+
+/// Images simulées après chaque coup, assez pour une chute d'un bout à l'autre de l'écran
+const SETTLE_FRAMES: usize = 120;
+
+/// Un coffre au sol et la porte rouge au bout du couloir
+pub const CHEST_LEVEL: &str = "name=Coffre
+111111111111111111
+------------------
+----&-------------
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+---C------------R-
+111111111111111111
+";
+
+pub struct Harness {
+    pub app: App,
+    level_end: ManualEventReader<LevelEndEvent>,
+    sound: ManualEventReader<SoundEvent>,
+    /// Fins de niveau vues depuis le début
+    pub ends: Vec<LevelEnd>,
+    /// Effets sonores demandés depuis le début
+    pub sounds: Vec<Sfx>,
+}
+
+impl Harness {
+    /// `map` a le format des fichiers `.lev`, lignes `clé=valeur` comprises
+    pub fn new(map: &str) -> Harness {
+        let mut app = App::new();
+        app
+            .add_plugins(MinimalPlugins)
+            .add_plugins(AssetPlugin::default())
+            // une image par tour d'horloge, et des sprites assez rapides pour finir chaque case en une image
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(1. / 60.)))
+            .insert_resource(MovementSpeed { player: 100_000., falling: 100_000., monster: 100_000. })
+            .init_asset::<Image>()
+            .add_plugins(LevelPlugin)
+            .add_plugins(PlayerPlugin)
+            .add_plugins(TickPlugin)
+            .add_plugins(HotReloadPlugin)
+            .add_plugins(EventLogPlugin)
+            .add_plugins(RealtimePlugin)
+            .insert_resource(SaveGame::load(Box::<MemoryStorage>::default()))
+            .add_state::<GameState>()
+            .add_state::<PauseState>()
+            .init_resource::<TexturesRessource>()
+            .init_resource::<LevelTransition>()
+            .init_resource::<ActionState>()
+            .init_resource::<InputQueue>()
+            .init_resource::<Score>()
+            .add_event::<LevelEndEvent>()
+            .add_event::<SoundEvent>()
+            .add_systems(Update, animate_entity.in_set(TurnSet::Animate).run_if(in_state(GameState::Game)));
+        #[cfg(feature = "dev")]
+        app.init_resource::<DevCheats>();
+
+        let handle = app.world.resource_mut::<Assets<LevelAsset>>().add(LevelAsset::parse(map));
+        app.world.resource_mut::<LevelMaps>().maps_handle = vec![handle];
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Game);
+
+        let mut harness = Harness { app, level_end: ManualEventReader::default(), sound: ManualEventReader::default(), ends: Vec::new(), sounds: Vec::new() };
+        harness.settle();
+        return harness;
+    }
+
+    /// Joue une action pendant une image, puis attend que tout soit posé
+    pub fn play(&mut self, action: Action) {
+        self.app.world.resource_mut::<ActionState>().press(action, true);
+        self.update();
+        *self.app.world.resource_mut::<ActionState>() = ActionState::default();
+        self.settle();
+    }
+
+    /// Remplace la carte comme si le fichier avait été modifié, puis attend que tout soit posé
+    pub fn edit_map(&mut self, map: &str) {
+        let handle = self.app.world.resource::<LevelMaps>().maps_handle[0].clone();
+        self.app.world.resource_mut::<Assets<LevelAsset>>().insert(handle, LevelAsset::parse(map));
+        self.settle();
+    }
+
+    /// Laisse passer le temps sans rien jouer, une image par soixantième de seconde
+    pub fn wait(&mut self, seconds: f32) {
+        for _ in 0..(seconds * 60.) as usize {
+            self.update();
+        }
+    }
+
+    /// Une chauve-souris comme celles des coffres, qui joue après les autres
+    pub fn spawn_bat(&mut self, game_x: i32, game_y: i32) {
+        self.app.world.run_system_once(move |mut commands: Commands, textures_ressource: Res<TexturesRessource>, mut simulation: ResMut<SimulationState>| {
+            spawn_bat(&mut commands, &textures_ressource, game_x, game_y, simulation.next_monster);
+            simulation.next_monster += 1;
+        });
+    }
+
+    pub fn settle(&mut self) {
+        for _ in 0..SETTLE_FRAMES {
+            self.update();
+        }
+    }
+
+    fn update(&mut self) {
+        self.app.update();
+        let events = self.app.world.resource::<Events<LevelEndEvent>>();
+        self.ends.extend(self.level_end.read(events).map(|event| event.kind));
+        let events = self.app.world.resource::<Events<SoundEvent>>();
+        self.sounds.extend(self.sound.read(events).map(|event| event.sfx));
+    }
+
+    pub fn player(&mut self) -> (i32, i32) {
+        let player = self.app.world.query::<&Player>().single(&self.app.world);
+        return (player.game_x.unwrap(), player.game_y.unwrap());
+    }
+
+    /// (x, y, ouvert, monstre sorti), triés par position
+    pub fn chests(&mut self) -> Vec<(i32, i32, bool, bool)> {
+        let mut chests: Vec<(i32, i32, bool, bool)> = self.app.world.query::<&Chest>().iter(&self.app.world)
+            .map(|chest| (chest.game_x, chest.game_y, chest.is_open, chest.has_spawn))
+            .collect();
+        chests.sort();
+        return chests;
+    }
+
+    /// Positions dans l'ordre d'apparition
+    pub fn monsters(&mut self) -> Vec<(i32, i32)> {
+        let mut monsters: Vec<(u32, i32, i32)> = self.app.world.query::<&Monster>().iter(&self.app.world)
+            .map(|monster| (monster.order(), monster.game_x(), monster.game_y()))
+            .collect();
+        monsters.sort();
+        return monsters.into_iter().map(|(_, game_x, game_y)| (game_x, game_y)).collect();
+    }
+
+    /// (tour, empreinte de l'état)
+    pub fn simulation(&self) -> (u64, u64) {
+        let simulation = self.app.world.resource::<SimulationState>();
+        return (simulation.tick, simulation.hash);
+    }
+
+    pub fn moves(&self) -> u32 {
+        return self.app.world.resource::<Score>().moves;
+    }
+}
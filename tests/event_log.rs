@@ -0,0 +1,66 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+
+use common::{Harness, CHEST_LEVEL};
+use dungeon::{Action, EventLog};
+use serde_json::Value;
+
+/// `name` : un fichier par test, ils tournent en parallèle
+fn read_log(game: &mut Harness, name: &str, actions: &[Action]) -> Vec<Value> {
+    let path = std::env::temp_dir().join(format!("dungeon-events-{}-{}.jsonl", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    game.app.world.insert_resource(EventLog::create(&path).unwrap());
+    for action in actions {
+        game.play(*action);
+    }
+    // fermer le fichier avant de le relire
+    game.app.world.insert_resource(EventLog::default());
+    let content = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    return content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+}
+
+#[test]
+fn moves_and_death_are_logged_as_json_lines() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    let lines = read_log(&mut game, "death", &[Action::MoveLeft, Action::Use, Action::MoveRight, Action::MoveRight, Action::MoveLeft]);
+
+    let events: Vec<&str> = lines.iter().map(|line| line["event"].as_str().unwrap()).collect();
+    assert_eq!(events, vec!["move", "move", "move", "move", "move", "death"]);
+
+    assert_eq!(lines[0]["action"], "MoveLeft");
+    assert_eq!((lines[0]["x"].as_i64(), lines[0]["y"].as_i64()), (Some(3), Some(1)));
+    assert_eq!(lines[1]["action"], "Use");
+    assert_eq!(lines[4]["moves"], 5);
+    assert_eq!(lines[5]["level"], 1);
+    assert_eq!(lines[5]["x"], 4);
+}
+
+/// Le joueur tombe sur la porte rouge, aucun coffre à ouvrir
+const DOOR_LEVEL: &str = "
+111111111111111111
+------------------
+----------------&-
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+----------------R-
+111111111111111111
+";
+
+#[test]
+fn completion_is_logged_once() {
+    let mut game = Harness::new(DOOR_LEVEL);
+    let lines = read_log(&mut game, "completion", &[Action::Use]);
+
+    let events: Vec<&str> = lines.iter().map(|line| line["event"].as_str().unwrap()).collect();
+    assert_eq!(events, vec!["move", "completion"]);
+    assert_eq!(lines[1]["moves"], 1);
+}
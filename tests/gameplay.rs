@@ -0,0 +1,186 @@
+mod common;
+
+use common::{Harness, CHEST_LEVEL};
+use dungeon::{Action, ChangeLevelEvent, Difficulty, LevelEnd, Sfx};
+
+/// Un trou dans le sol, le joueur retombe du haut de l'écran sur le mur du milieu
+const WRAP_LEVEL: &str = "
+11111-111111111111
+------------------
+------------------
+------------------
+------------------
+-----1------------
+------------------
+------------------
+------------------
+-----&------------
+------------------
+11111-111111111111
+";
+
+/// Couloir vide joué en temps réel
+const REALTIME_LEVEL: &str = "mode=realtime
+111111111111111111
+------------------
+----&-------------
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+----------------R-
+111111111111111111
+";
+
+const TELEPORT_LEVEL: &str = "
+111111111111111111
+------------------
+------------------
+------------------
+------------------
+--------------B---
+--------------1---
+------------------
+------------------
+------------------
+--&B------------R-
+111111111111111111
+";
+
+#[test]
+fn player_falls_to_the_ground() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    assert_eq!(game.player(), (4, 1));
+    assert_eq!(game.moves(), 0);
+}
+
+#[test]
+fn player_walks_and_stops_at_the_screen_edge() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    game.play(Action::MoveRight);
+    assert_eq!(game.player(), (5, 1));
+    for _ in 0..6 {
+        game.play(Action::MoveLeft);
+    }
+    // le dernier pas compte mais le joueur reste dans l'écran
+    assert_eq!(game.player(), (0, 1));
+    assert_eq!(game.moves(), 7);
+}
+
+#[test]
+fn falling_below_the_screen_wraps_to_the_top() {
+    let mut game = Harness::new(WRAP_LEVEL);
+    assert_eq!(game.player(), (5, 7));
+}
+
+#[test]
+fn blue_door_teleports_to_the_other_one() {
+    let mut game = Harness::new(TELEPORT_LEVEL);
+    game.play(Action::MoveRight);
+    assert_eq!(game.player(), (3, 1));
+    game.play(Action::Use);
+    assert_eq!(game.player(), (14, 6));
+    assert_eq!(game.moves(), 2);
+}
+
+#[test]
+fn using_a_chest_opens_it() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    game.play(Action::MoveLeft);
+    game.play(Action::Use);
+    assert_eq!(game.chests(), vec![(3, 1, true, false)]);
+    // rien ne sort tant que le joueur est sur le coffre
+    assert!(game.monsters().is_empty());
+}
+
+#[test]
+fn bat_spawns_when_leaving_an_open_chest_and_follows_the_player() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    game.play(Action::MoveLeft);
+    game.play(Action::Use);
+    game.play(Action::MoveRight);
+    assert_eq!(game.chests(), vec![(3, 1, true, true)]);
+    assert_eq!(game.monsters(), vec![(3, 1)]);
+
+    game.play(Action::MoveRight);
+    assert_eq!(game.player(), (5, 1));
+    assert_eq!(game.monsters(), vec![(4, 1)]);
+    assert!(game.ends.is_empty());
+}
+
+#[test]
+fn walking_into_a_bat_kills_the_player() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    game.play(Action::MoveLeft);
+    game.play(Action::Use);
+    game.play(Action::MoveRight);
+    game.play(Action::MoveRight);
+    game.play(Action::MoveLeft);
+    assert_eq!(game.ends.first(), Some(&LevelEnd::Died));
+}
+
+#[test]
+fn red_door_needs_every_chest_open() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    for _ in 0..12 {
+        game.play(Action::MoveRight);
+    }
+    assert_eq!(game.player(), (16, 1));
+    game.play(Action::Use);
+    assert!(game.ends.is_empty());
+
+    for _ in 0..13 {
+        game.play(Action::MoveLeft);
+    }
+    game.play(Action::Use);
+    // la chauve-souris suit le joueur sans jamais le rattraper
+    for _ in 0..13 {
+        game.play(Action::MoveRight);
+    }
+    game.play(Action::Use);
+    assert_eq!(game.ends, vec![LevelEnd::Completed]);
+}
+
+#[test]
+fn two_runs_of_the_same_moves_hash_the_same_states() {
+    let mut first = Harness::new(CHEST_LEVEL);
+    let mut second = Harness::new(CHEST_LEVEL);
+    for action in [Action::MoveLeft, Action::Use, Action::MoveRight, Action::MoveRight, Action::MoveRight, Action::MoveLeft] {
+        first.play(action);
+        second.play(action);
+        assert_eq!(first.simulation(), second.simulation(), "after {:?}", action);
+    }
+    assert!(first.simulation().0 > 0 && first.simulation().1 != 0);
+}
+
+#[test]
+fn bats_play_on_their_own_in_real_time() {
+    let mut game = Harness::new(REALTIME_LEVEL);
+    game.spawn_bat(10, 1);
+    // un tour et demi au plus : la chauve-souris s'approche sans que le joueur bouge
+    game.wait(Difficulty::Normal.tick_interval() * 1.2);
+    let (bat_x, _) = game.monsters()[0];
+    assert!((5..10).contains(&bat_x), "bat at {}", bat_x);
+    assert!(game.ends.is_empty());
+    assert_eq!(game.player(), (4, 1));
+
+    game.wait(Difficulty::Normal.tick_interval() * 6.);
+    assert_eq!(game.ends.first(), Some(&LevelEnd::Died));
+    assert_eq!(game.moves(), 0);
+}
+
+#[test]
+fn restarting_mid_fall_plays_the_next_fall_sound() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    game.app.world.send_event(ChangeLevelEvent { new_level: false });
+    game.wait(0.1);
+    assert!(game.player().1 > 1, "the player should still be falling");
+
+    game.app.world.send_event(ChangeLevelEvent { new_level: false });
+    game.sounds.clear();
+    game.settle();
+    assert_eq!(game.sounds, vec![Sfx::Fall, Sfx::Land]);
+}
@@ -0,0 +1,83 @@
+//! Navigation au clavier dans le menu, sans fenêtre ni rendu.
+
+#![allow(clippy::needless_return)]
+
+use bevy::{app::AppExit, ecs::event::ManualEventReader, prelude::*};
+use dungeon::*;
+
+fn menu_app() -> App {
+    let mut app = App::new();
+    app
+        .add_plugins(MinimalPlugins)
+        .add_state::<GameState>()
+        .add_state::<PauseState>()
+        .add_plugins(MenuPlugin)
+        .insert_resource(SaveGame::load(Box::<MemoryStorage>::default()))
+        .insert_resource(Controls::load(Box::<MemoryStorage>::default()))
+        .insert_resource(CurrentLevel { level: 1 })
+        .init_resource::<Rebinding>()
+        .init_resource::<ActionState>()
+        .init_resource::<GamepadActions>()
+        .init_resource::<Input<KeyCode>>()
+        .init_resource::<Score>()
+        .init_resource::<RunStats>()
+        .init_resource::<LevelMaps>()
+        .add_event::<ChangeLevelEvent>();
+    #[cfg(feature = "dev")]
+    app.init_resource::<Console>();
+
+    app.world.resource_mut::<NextState<GameState>>().set(GameState::Menu);
+    app.update();
+    return app;
+}
+
+fn open_page(app: &mut App, page: MenuPage, index: usize) {
+    *app.world.resource_mut::<MenuPage>() = page;
+    app.update();
+    app.world.resource_mut::<MenuSelection>().index = index;
+}
+
+/// Une image avec `key` appuyée, sans `InputPlugin` pour la relâcher
+fn press(app: &mut App, key: KeyCode) {
+    app.world.resource_mut::<Input<KeyCode>>().press(key);
+    app.update();
+    let mut input = app.world.resource_mut::<Input<KeyCode>>();
+    input.release(key);
+    input.clear();
+    app.update();
+}
+
+#[test]
+fn left_changes_the_selected_setting() {
+    let mut app = menu_app();
+    open_page(&mut app, MenuPage::Settings, 0);
+    let volume = app.world.resource::<SaveGame>().data.settings.music_volume;
+
+    press(&mut app, KeyCode::Left);
+
+    assert!(app.world.resource::<SaveGame>().data.settings.music_volume < volume);
+}
+
+#[test]
+fn right_does_not_start_a_locked_level() {
+    let mut app = menu_app();
+    // seul le niveau 1 est débloqué, le deuxième bouton est le niveau 2
+    open_page(&mut app, MenuPage::LevelSelect, 1);
+
+    press(&mut app, KeyCode::Right);
+
+    assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::Menu);
+    assert_eq!(app.world.resource::<CurrentLevel>().level, 1);
+}
+
+#[test]
+fn right_does_not_quit() {
+    let mut app = menu_app();
+    open_page(&mut app, MenuPage::Main, 4);
+    let mut app_exit = ManualEventReader::<AppExit>::default();
+
+    press(&mut app, KeyCode::Right);
+
+    assert_eq!(app_exit.read(app.world.resource::<Events<AppExit>>()).count(), 0);
+    assert_eq!(*app.world.resource::<MenuPage>(), MenuPage::Main);
+}
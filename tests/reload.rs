@@ -0,0 +1,99 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::{Harness, CHEST_LEVEL};
+use dungeon::{Action, ChangeLevelEvent, HotReload};
+
+/// Un deuxième coffre plus loin dans le couloir
+const MORE_CHESTS: &str = "
+111111111111111111
+------------------
+----&-------------
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+---C----C-------R-
+111111111111111111
+";
+
+/// Un mur là où se tient le joueur, qui apparaît maintenant au-dessus de la porte
+const WALL_ON_PLAYER: &str = "
+111111111111111111
+------------------
+----------------&-
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+---C--1---------R-
+111111111111111111
+";
+
+const TWO_PLAYERS: &str = "
+111111111111111111
+------------------
+----&-----&-------
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+------------------
+---C------------R-
+111111111111111111
+";
+
+fn errors(game: &Harness) -> Vec<String> {
+    return game.app.world.resource::<HotReload>().errors.clone();
+}
+
+#[test]
+fn edited_map_keeps_the_player_in_place() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    game.play(Action::MoveRight);
+    game.play(Action::MoveRight);
+    game.edit_map(MORE_CHESTS);
+    assert_eq!(game.player(), (6, 1));
+    assert_eq!(game.chests(), vec![(3, 1, false, false), (8, 1, false, false)]);
+    assert!(errors(&game).is_empty());
+}
+
+#[test]
+fn wall_on_the_player_restarts_the_level() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    game.play(Action::MoveRight);
+    game.play(Action::MoveRight);
+    game.edit_map(WALL_ON_PLAYER);
+    assert_eq!(game.player(), (16, 1));
+    assert_eq!(game.chests(), vec![(3, 1, false, false)]);
+}
+
+#[test]
+fn invalid_map_keeps_the_previous_level() {
+    let mut game = Harness::new(CHEST_LEVEL);
+    game.play(Action::MoveLeft);
+    game.play(Action::Use);
+    game.edit_map(TWO_PLAYERS);
+    assert_eq!(errors(&game), vec!["2 players instead of 1".to_owned()]);
+    assert_eq!(game.player(), (3, 1));
+    assert_eq!(game.chests(), vec![(3, 1, true, false)]);
+
+    // recommencer reconstruit la dernière carte valide
+    game.app.world.send_event(ChangeLevelEvent { new_level: false });
+    game.settle();
+    assert_eq!(game.player(), (4, 1));
+    assert_eq!(game.chests(), vec![(3, 1, false, false)]);
+
+    game.edit_map(MORE_CHESTS);
+    assert!(errors(&game).is_empty());
+    assert_eq!(game.chests(), vec![(3, 1, false, false), (8, 1, false, false)]);
+}
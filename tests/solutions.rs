@@ -0,0 +1,63 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use common::Harness;
+use dungeon::*;
+
+fn map_dir() -> PathBuf {
+    return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets/map");
+}
+
+/// Chaque niveau du jeu et sa solution de référence
+fn shipped_levels() -> Vec<(PathBuf, String, Vec<Move>)> {
+    let mut levels = Vec::new();
+    for level in 1..=NB_LEVEL {
+        let path = map_dir().join(format!("level-{}.lev", level));
+        let map = fs::read_to_string(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+        let solution_path = path.with_extension(SOLUTION_EXTENSION);
+        let solution = fs::read_to_string(&solution_path).unwrap_or_else(|error| panic!("{}: {}", solution_path.display(), error));
+        let moves = parse_moves(&solution).unwrap_or_else(|error| panic!("{}: {}", solution_path.display(), error));
+        levels.push((path, map, moves));
+    }
+    return levels;
+}
+
+#[test]
+fn every_level_file_is_shipped() {
+    let count = fs::read_dir(map_dir()).unwrap()
+        .filter(|entry| entry.as_ref().unwrap().path().extension().is_some_and(|extension| extension == "lev"))
+        .count();
+    assert_eq!(count, NB_LEVEL as usize);
+}
+
+#[test]
+fn solutions_complete_their_level_in_the_simulation() {
+    for (path, map, moves) in shipped_levels() {
+        let grid = LevelGrid::parse(&LevelAsset::parse(&map).map).unwrap();
+        assert_eq!(grid.validate(), Vec::new(), "{}", path.display());
+        let (result, played) = Simulation::new(&grid).replay(&moves);
+        assert_eq!(result, StepResult::Completed, "{}", path.display());
+        assert_eq!(played, moves.len(), "{}: moves left after the end", path.display());
+    }
+}
+
+#[test]
+fn solutions_complete_their_level_in_the_game() {
+    for (path, map, moves) in shipped_levels() {
+        let mut game = Harness::new(&map);
+        for action in &moves {
+            assert!(game.ends.is_empty(), "{}: level ended early with {:?}", path.display(), game.ends);
+            game.play(match action {
+                Move::Left => Action::MoveLeft,
+                Move::Right => Action::MoveRight,
+                Move::Use => Action::Use,
+            });
+        }
+        assert_eq!(game.ends, vec![LevelEnd::Completed], "{}", path.display());
+        assert!(game.chests().iter().all(|(_, _, is_open, _)| *is_open), "{}", path.display());
+        assert_eq!(game.moves(), moves.len() as u32, "{}", path.display());
+    }
+}